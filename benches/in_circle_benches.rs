@@ -63,4 +63,22 @@ mod tests {
             t_box.locate(test_box)
         });
     }
+
+    #[bench]
+    fn bench_triangle_in_circle_tests(b: &mut Bencher) {
+        let t = Triangle::new(
+            Point2D::new(0.0, 0.0),
+            Point2D::new(0.0, 1.0),
+            Point2D::new(1.0, 1.0),
+        );
+
+        let test_points = (0..1000).map(|i| Point2D::new((i % 40) as f64 * 0.05, (i / 40) as f64 * 0.05)).collect::<Vec<_>>();
+
+        b.iter(|| {
+            let t_box = black_box(&t);
+            let test_box = black_box(&test_points);
+
+            t_box.in_circle_tests(test_box)
+        });
+    }
 }
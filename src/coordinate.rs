@@ -0,0 +1,305 @@
+//! The coordinate types the predicates can be evaluated on.
+//!
+//! Each predicate returns a value with the exact sign of the corresponding determinant, so the
+//! results agree with those on `f64` coordinates whenever the inputs are representable in both.
+//!
+//! * `f64` uses the adaptive predicates directly.
+//! * `f32` is converted to `f64` without rounding, then evaluated like `f64`.
+//! * `i32` evaluates the orientations in 128 bit integer arithmetic. The in-circle tests would
+//!   overflow it, and convert to `f64` without rounding instead.
+//! * `i64` evaluates all predicates with exact expansions, since neither 128 bit integers nor a
+//!   single `f64` hold the products.
+
+use geometry::*;
+use error::GeometryError;
+use expansion::{Expansion, determinant};
+use predicates;
+
+pub trait Coordinate: Copy {
+    /// Returns an error if the coordinate is NaN or infinite.
+    fn validate(self) -> Result<(), GeometryError>;
+
+    /// Positive if the points are in counter-clockwise order, negative if clockwise, and zero
+    /// if they are collinear.
+    fn orient2d(pa: &Point2D<Self>, pb: &Point2D<Self>, pc: &Point2D<Self>) -> f64;
+
+    /// Positive if `pd` lies below the plane through the other points, which appear in
+    /// counter-clockwise order seen from above, and zero if the points are coplanar.
+    fn orient3d(pa: &Point3D<Self>, pb: &Point3D<Self>, pc: &Point3D<Self>, pd: &Point3D<Self>) -> f64;
+
+    /// Positive if `pd` lies inside the circle through the other points, when those are in
+    /// counter-clockwise order, and zero if the points are cocircular.
+    fn incircle(pa: &Point2D<Self>, pb: &Point2D<Self>, pc: &Point2D<Self>, pd: &Point2D<Self>) -> f64;
+
+    /// Positive if `pe` lies inside the sphere through the other points, when `orient3d` of
+    /// those is positive, and zero if the points are cospherical.
+    fn insphere(pa: &Point3D<Self>, pb: &Point3D<Self>, pc: &Point3D<Self>, pd: &Point3D<Self>, pe: &Point3D<Self>) -> f64;
+
+    /// Evaluates `orient2d(pa, pb, pc)` for each point of `pcs`, writing the results to `out`.
+    fn orient2d_batch(pa: &Point2D<Self>, pb: &Point2D<Self>, pcs: &[Point2D<Self>], out: &mut [f64]) {
+        assert_eq!(pcs.len(), out.len());
+        for (pc, det) in pcs.iter().zip(out) {
+            *det = Self::orient2d(pa, pb, pc);
+        }
+    }
+
+    /// Evaluates `orient3d(pa, pb, pc, pd)` for each point of `pds`, writing the results to `out`.
+    fn orient3d_batch(pa: &Point3D<Self>, pb: &Point3D<Self>, pc: &Point3D<Self>, pds: &[Point3D<Self>], out: &mut [f64]) {
+        assert_eq!(pds.len(), out.len());
+        for (pd, det) in pds.iter().zip(out) {
+            *det = Self::orient3d(pa, pb, pc, pd);
+        }
+    }
+
+    /// Evaluates `incircle(pa, pb, pc, pd)` for each point of `pds`, writing the results to `out`.
+    fn incircle_batch(pa: &Point2D<Self>, pb: &Point2D<Self>, pc: &Point2D<Self>, pds: &[Point2D<Self>], out: &mut [f64]) {
+        assert_eq!(pds.len(), out.len());
+        for (pd, det) in pds.iter().zip(out) {
+            *det = Self::incircle(pa, pb, pc, pd);
+        }
+    }
+
+    /// Evaluates `insphere(pa, pb, pc, pd, pe)` for each point of `pes`, writing the results to
+    /// `out`.
+    fn insphere_batch(pa: &Point3D<Self>, pb: &Point3D<Self>, pc: &Point3D<Self>, pd: &Point3D<Self>, pes: &[Point3D<Self>], out: &mut [f64]) {
+        assert_eq!(pes.len(), out.len());
+        for (pe, det) in pes.iter().zip(out) {
+            *det = Self::insphere(pa, pb, pc, pd, pe);
+        }
+    }
+}
+
+fn validate_float(x: f64) -> Result<(), GeometryError> {
+    if x.is_nan() {
+        Err(GeometryError::NotANumber)
+    } else if x.is_infinite() {
+        Err(GeometryError::Infinite)
+    } else {
+        Ok(())
+    }
+}
+
+impl Coordinate for f64 {
+    fn validate(self) -> Result<(), GeometryError> {
+        validate_float(self)
+    }
+
+    fn orient2d(pa: &Point2D, pb: &Point2D, pc: &Point2D) -> f64 {
+        predicates::orient2d(pa, pb, pc)
+    }
+
+    fn orient3d(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D) -> f64 {
+        predicates::orient3d(pa, pb, pc, pd)
+    }
+
+    fn incircle(pa: &Point2D, pb: &Point2D, pc: &Point2D, pd: &Point2D) -> f64 {
+        predicates::incircle(pa, pb, pc, pd)
+    }
+
+    fn insphere(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pe: &Point3D) -> f64 {
+        predicates::insphere(pa, pb, pc, pd, pe)
+    }
+
+    fn orient2d_batch(pa: &Point2D, pb: &Point2D, pcs: &[Point2D], out: &mut [f64]) {
+        predicates::orient2d_batch(pa, pb, pcs, out)
+    }
+
+    fn orient3d_batch(pa: &Point3D, pb: &Point3D, pc: &Point3D, pds: &[Point3D], out: &mut [f64]) {
+        predicates::orient3d_batch(pa, pb, pc, pds, out)
+    }
+
+    fn incircle_batch(pa: &Point2D, pb: &Point2D, pc: &Point2D, pds: &[Point2D], out: &mut [f64]) {
+        predicates::incircle_batch(pa, pb, pc, pds, out)
+    }
+
+    fn insphere_batch(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pes: &[Point3D], out: &mut [f64]) {
+        predicates::insphere_batch(pa, pb, pc, pd, pes, out)
+    }
+}
+
+fn to_f64_2d<T: Copy + Into<f64>>(p: &Point2D<T>) -> Point2D {
+    Point2D::new(p.x.into(), p.y.into())
+}
+
+fn to_f64_3d<T: Copy + Into<f64>>(p: &Point3D<T>) -> Point3D {
+    Point3D::new(p.x.into(), p.y.into(), p.z.into())
+}
+
+impl Coordinate for f32 {
+    fn validate(self) -> Result<(), GeometryError> {
+        validate_float(f64::from(self))
+    }
+
+    fn orient2d(pa: &Point2D<f32>, pb: &Point2D<f32>, pc: &Point2D<f32>) -> f64 {
+        predicates::orient2d(&to_f64_2d(pa), &to_f64_2d(pb), &to_f64_2d(pc))
+    }
+
+    fn orient3d(pa: &Point3D<f32>, pb: &Point3D<f32>, pc: &Point3D<f32>, pd: &Point3D<f32>) -> f64 {
+        predicates::orient3d(&to_f64_3d(pa), &to_f64_3d(pb), &to_f64_3d(pc), &to_f64_3d(pd))
+    }
+
+    fn incircle(pa: &Point2D<f32>, pb: &Point2D<f32>, pc: &Point2D<f32>, pd: &Point2D<f32>) -> f64 {
+        predicates::incircle(&to_f64_2d(pa), &to_f64_2d(pb), &to_f64_2d(pc), &to_f64_2d(pd))
+    }
+
+    fn insphere(pa: &Point3D<f32>, pb: &Point3D<f32>, pc: &Point3D<f32>, pd: &Point3D<f32>, pe: &Point3D<f32>) -> f64 {
+        predicates::insphere(&to_f64_3d(pa), &to_f64_3d(pb), &to_f64_3d(pc), &to_f64_3d(pd), &to_f64_3d(pe))
+    }
+}
+
+impl Coordinate for i32 {
+    fn validate(self) -> Result<(), GeometryError> {
+        Ok(())
+    }
+
+    fn orient2d(pa: &Point2D<i32>, pb: &Point2D<i32>, pc: &Point2D<i32>) -> f64 {
+        let d = |a: i32, b: i32| i128::from(a) - i128::from(b);
+        let (acx, acy) = (d(pa.x, pc.x), d(pa.y, pc.y));
+        let (bcx, bcy) = (d(pb.x, pc.x), d(pb.y, pc.y));
+
+        (acx * bcy - acy * bcx) as f64
+    }
+
+    fn orient3d(pa: &Point3D<i32>, pb: &Point3D<i32>, pc: &Point3D<i32>, pd: &Point3D<i32>) -> f64 {
+        let d = |a: i32, b: i32| i128::from(a) - i128::from(b);
+        let (adx, ady, adz) = (d(pa.x, pd.x), d(pa.y, pd.y), d(pa.z, pd.z));
+        let (bdx, bdy, bdz) = (d(pb.x, pd.x), d(pb.y, pd.y), d(pb.z, pd.z));
+        let (cdx, cdy, cdz) = (d(pc.x, pd.x), d(pc.y, pd.y), d(pc.z, pd.z));
+
+        let det = adx * (bdy * cdz - bdz * cdy)
+                + bdx * (cdy * adz - cdz * ady)
+                + cdx * (ady * bdz - adz * bdy);
+
+        det as f64
+    }
+
+    fn incircle(pa: &Point2D<i32>, pb: &Point2D<i32>, pc: &Point2D<i32>, pd: &Point2D<i32>) -> f64 {
+        predicates::incircle(&to_f64_2d(pa), &to_f64_2d(pb), &to_f64_2d(pc), &to_f64_2d(pd))
+    }
+
+    fn insphere(pa: &Point3D<i32>, pb: &Point3D<i32>, pc: &Point3D<i32>, pd: &Point3D<i32>, pe: &Point3D<i32>) -> f64 {
+        predicates::insphere(&to_f64_3d(pa), &to_f64_3d(pb), &to_f64_3d(pc), &to_f64_3d(pd), &to_f64_3d(pe))
+    }
+}
+
+/// The exact difference of two integers as an expansion. It has at most 65 bits, so the two
+/// components never round.
+fn difference(a: i64, b: i64) -> Expansion {
+    let d = i128::from(a) - i128::from(b);
+    let high = d as f64;
+
+    Expansion::sum(high, (d - high as i128) as f64)
+}
+
+/// The row of a point in the determinant, relative to `origin` and optionally lifted to
+/// `x^2 + y^2 (+ z^2)`.
+fn row(p: &[i64], origin: &[i64], lift: bool) -> Vec<Expansion> {
+    let mut row = p.iter().zip(origin).map(|(&a, &b)| difference(a, b)).collect::<Vec<_>>();
+    if lift {
+        let square = row.iter().fold(Expansion::default(), |sum, x| sum + x * x);
+        row.push(square);
+    }
+
+    row
+}
+
+fn orient_i64(points: &[&[i64]], lift: bool) -> f64 {
+    let (origin, rest) = points.split_last().unwrap();
+    let matrix = rest.iter().map(|p| row(p, origin, lift)).collect::<Vec<_>>();
+
+    determinant(&matrix).approximate()
+}
+
+impl Coordinate for i64 {
+    fn validate(self) -> Result<(), GeometryError> {
+        Ok(())
+    }
+
+    fn orient2d(pa: &Point2D<i64>, pb: &Point2D<i64>, pc: &Point2D<i64>) -> f64 {
+        orient_i64(&[&[pa.x, pa.y], &[pb.x, pb.y], &[pc.x, pc.y]], false)
+    }
+
+    fn orient3d(pa: &Point3D<i64>, pb: &Point3D<i64>, pc: &Point3D<i64>, pd: &Point3D<i64>) -> f64 {
+        orient_i64(&[&[pa.x, pa.y, pa.z], &[pb.x, pb.y, pb.z], &[pc.x, pc.y, pc.z], &[pd.x, pd.y, pd.z]], false)
+    }
+
+    fn incircle(pa: &Point2D<i64>, pb: &Point2D<i64>, pc: &Point2D<i64>, pd: &Point2D<i64>) -> f64 {
+        orient_i64(&[&[pa.x, pa.y], &[pb.x, pb.y], &[pc.x, pc.y], &[pd.x, pd.y]], true)
+    }
+
+    fn insphere(pa: &Point3D<i64>, pb: &Point3D<i64>, pc: &Point3D<i64>, pd: &Point3D<i64>, pe: &Point3D<i64>) -> f64 {
+        orient_i64(&[&[pa.x, pa.y, pa.z], &[pb.x, pb.y, pb.z], &[pc.x, pc.y, pc.z], &[pd.x, pd.y, pd.z], &[pe.x, pe.y, pe.z]], true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::quickcheck;
+
+    fn sign(x: f64) -> i8 {
+        if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 }
+    }
+
+    /// The signs of all predicates on the first five points.
+    fn signs<T: Coordinate>(c: &[(i8, i8, i8)], convert: fn(i8) -> T) -> [i8; 4] {
+        let p2 = |i: usize| Point2D::new(convert(c[i].0), convert(c[i].1));
+        let p3 = |i: usize| Point3D::new(convert(c[i].0), convert(c[i].1), convert(c[i].2));
+
+        [sign(T::orient2d(&p2(0), &p2(1), &p2(2))),
+         sign(T::orient3d(&p3(0), &p3(1), &p3(2), &p3(3))),
+         sign(T::incircle(&p2(0), &p2(1), &p2(2), &p2(3))),
+         sign(T::insphere(&p3(0), &p3(1), &p3(2), &p3(3), &p3(4)))]
+    }
+
+    #[test]
+    fn all_coordinate_types_agree() {
+        fn all_coordinate_types_agree(c: Vec<(i8, i8, i8)>) -> bool {
+            // A coarse grid, so that degenerate configurations are common
+            let c = c.into_iter().map(|(x, y, z)| (x % 4, y % 4, z % 4)).collect::<Vec<_>>();
+            if c.len() < 5 {
+                return true;
+            }
+
+            let expected = signs(&c, f64::from);
+            signs(&c, f32::from) == expected && signs(&c, i32::from) == expected && signs(&c, i64::from) == expected
+        }
+        quickcheck(all_coordinate_types_agree as fn(c: Vec<(i8, i8, i8)>) -> bool)
+    }
+
+    #[test]
+    fn large_integers_are_exact() {
+        fn large_integers_are_exact(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> bool {
+            // Spread the small generated values over 61 bits, so that the determinant fits into
+            // 128 bits and the point `2b - a` on the line through `a` and `b` cannot overflow
+            let large = |x: i64| x.wrapping_mul(0x0123_4567_89ab_cdef) >> 3;
+            let (a, b, c) = ((large(a.0), large(a.1)), (large(b.0), large(b.1)), (large(c.0), large(c.1)));
+            let det = (a.0 - c.0) as i128 * (b.1 - c.1) as i128 - (a.1 - c.1) as i128 * (b.0 - c.0) as i128;
+
+            let p = |p: (i64, i64)| Point2D::new(p.0, p.1);
+            let collinear = Point2D::new(a.0 + 2 * (b.0 - a.0), a.1 + 2 * (b.1 - a.1));
+
+            sign(i64::orient2d(&p(a), &p(b), &p(c))) == det.signum() as i8
+                && i64::orient2d(&p(a), &p(b), &collinear) == 0.0
+                && i64::orient2d(&collinear, &p(a), &p(b)) == 0.0
+        }
+        quickcheck(large_integers_are_exact as fn(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> bool)
+    }
+
+    #[test]
+    fn full_range_integers() {
+        let (min, max) = (i32::MIN, i32::MAX);
+        let a = Point2D::new(min, min);
+        let b = Point2D::new(max, max);
+
+        assert_eq!(i32::orient2d(&a, &b, &Point2D::new(0, 0)), 0.0);
+        assert!(i32::orient2d(&a, &b, &Point2D::new(0, 1)) > 0.0);
+        assert!(i32::orient2d(&a, &b, &Point2D::new(1, 0)) < 0.0);
+
+        let inside = i32::incircle(&a, &Point2D::new(max, min), &b, &Point2D::new(0, 0));
+        let wide = |p: Point2D<i32>| Point2D::new(i64::from(p.x), i64::from(p.y));
+        assert!(inside > 0.0);
+        assert!(i64::incircle(&wide(a), &Point2D::new(i64::from(max), i64::from(min)), &wide(b), &Point2D::new(0, 0)) > 0.0);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VertexHandle(usize);
+
+/// Handle of a directed half-edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeHandle(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FaceHandle(usize);
+
+impl VertexHandle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl EdgeHandle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl FaceHandle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Vertex<P> {
+    point: P,
+    edge: Option<EdgeHandle>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HalfEdge {
+    origin: VertexHandle,
+    twin: Option<EdgeHandle>,
+    next: EdgeHandle,
+    face: FaceHandle,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    edge: EdgeHandle,
+}
+
+/// An index based half-edge data structure (doubly connected edge list).
+///
+/// Every face is a closed loop of half-edges linked by `next`. Half-edges on the boundary of the
+/// mesh have no twin, there is no explicit outer face. Removed elements leave a hole that is
+/// reused by later insertions, so handles stay valid until the element they refer to is removed.
+#[derive(Debug, Clone)]
+pub struct Dcel<P> {
+    vertices: Vec<Option<Vertex<P>>>,
+    edges: Vec<Option<HalfEdge>>,
+    faces: Vec<Option<Face>>,
+    free_vertices: Vec<usize>,
+    free_edges: Vec<usize>,
+    free_faces: Vec<usize>,
+    edge_map: HashMap<(VertexHandle, VertexHandle), EdgeHandle>,
+}
+
+impl<P: Copy> Default for Dcel<P> {
+    fn default() -> Dcel<P> {
+        Dcel::new()
+    }
+}
+
+fn allocate<T>(items: &mut Vec<Option<T>>, free: &mut Vec<usize>, item: T) -> usize {
+    match free.pop() {
+        Some(i) => { items[i] = Some(item); i },
+        None => { items.push(Some(item)); items.len() - 1 },
+    }
+}
+
+impl<P: Copy> Dcel<P> {
+    pub fn new() -> Dcel<P> {
+        Dcel {
+            vertices: Vec::new(),
+            edges: Vec::new(),
+            faces: Vec::new(),
+            free_vertices: Vec::new(),
+            free_edges: Vec::new(),
+            free_faces: Vec::new(),
+            edge_map: HashMap::new(),
+        }
+    }
+
+    pub fn add_vertex(&mut self, point: P) -> VertexHandle {
+        VertexHandle(allocate(&mut self.vertices, &mut self.free_vertices, Vertex { point, edge: None }))
+    }
+
+    /// Removes a vertex. All faces around it have to be removed first.
+    pub fn remove_vertex(&mut self, v: VertexHandle) {
+        debug_assert!(self.vertex(v).edge.is_none());
+        self.vertices[v.0] = None;
+        self.free_vertices.push(v.0);
+    }
+
+    /// Adds a face bounded by the given vertices, linking each new half-edge with the existing
+    /// half-edge in the opposite direction, if any.
+    pub fn add_face(&mut self, vertices: &[VertexHandle]) -> FaceHandle {
+        let n = vertices.len();
+        let placeholder = HalfEdge { origin: vertices[0], twin: None, next: EdgeHandle(0), face: FaceHandle(0) };
+        let handles = (0..n).map(|_| {
+            EdgeHandle(allocate(&mut self.edges, &mut self.free_edges, placeholder))
+        }).collect::<Vec<_>>();
+
+        let face = FaceHandle(allocate(&mut self.faces, &mut self.free_faces, Face { edge: handles[0] }));
+
+        for i in 0..n {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            let e = handles[i];
+            let twin = self.edge_map.get(&(b, a)).cloned();
+
+            debug_assert!(!self.edge_map.contains_key(&(a, b)));
+
+            self.edges[e.0] = Some(HalfEdge { origin: a, twin, next: handles[(i + 1) % n], face });
+            self.edge_map.insert((a, b), e);
+
+            if let Some(t) = twin {
+                self.half_edge_mut(t).twin = Some(e);
+            }
+
+            let vertex = self.vertices[a.0].as_mut().unwrap();
+            if vertex.edge.is_none() {
+                vertex.edge = Some(e);
+            }
+        }
+
+        face
+    }
+
+    /// Removes a face and its half-edges. The twins of its half-edges become boundary edges.
+    pub fn remove_face(&mut self, f: FaceHandle) {
+        let edges = self.face_edges(f);
+
+        for &e in &edges {
+            let a = self.origin(e);
+
+            if self.vertex(a).edge == Some(e) {
+                // Any other outgoing edge of the vertex keeps it connected to the mesh
+                let replacement = self.twin(self.prev(e)).or_else(|| self.twin(e).map(|t| self.next(t)));
+                self.vertices[a.0].as_mut().unwrap().edge = replacement;
+            }
+        }
+
+        for &e in &edges {
+            let key = (self.origin(e), self.destination(e));
+
+            if let Some(t) = self.twin(e) {
+                self.half_edge_mut(t).twin = None;
+            }
+
+            self.edge_map.remove(&key);
+        }
+
+        for &e in &edges {
+            self.edges[e.0] = None;
+            self.free_edges.push(e.0);
+        }
+
+        self.faces[f.0] = None;
+        self.free_faces.push(f.0);
+    }
+
+    pub fn point(&self, v: VertexHandle) -> P {
+        self.vertex(v).point
+    }
+
+    pub fn vertices(&self) -> Vec<VertexHandle> {
+        (0..self.vertices.len()).filter(|&i| self.vertices[i].is_some()).map(VertexHandle).collect()
+    }
+
+    pub fn edges(&self) -> Vec<EdgeHandle> {
+        (0..self.edges.len()).filter(|&i| self.edges[i].is_some()).map(EdgeHandle).collect()
+    }
+
+    pub fn faces(&self) -> Vec<FaceHandle> {
+        (0..self.faces.len()).filter(|&i| self.faces[i].is_some()).map(FaceHandle).collect()
+    }
+
+    pub fn contains_vertex(&self, v: VertexHandle) -> bool {
+        self.vertices.get(v.0).is_some_and(|v| v.is_some())
+    }
+
+    pub fn contains_face(&self, f: FaceHandle) -> bool {
+        self.faces.get(f.0).is_some_and(|f| f.is_some())
+    }
+
+    pub fn origin(&self, e: EdgeHandle) -> VertexHandle {
+        self.half_edge(e).origin
+    }
+
+    pub fn destination(&self, e: EdgeHandle) -> VertexHandle {
+        self.origin(self.next(e))
+    }
+
+    pub fn next(&self, e: EdgeHandle) -> EdgeHandle {
+        self.half_edge(e).next
+    }
+
+    pub fn prev(&self, e: EdgeHandle) -> EdgeHandle {
+        let mut p = e;
+        while self.next(p) != e {
+            p = self.next(p);
+        }
+        p
+    }
+
+    pub fn twin(&self, e: EdgeHandle) -> Option<EdgeHandle> {
+        self.half_edge(e).twin
+    }
+
+    pub fn face(&self, e: EdgeHandle) -> FaceHandle {
+        self.half_edge(e).face
+    }
+
+    /// One of the half-edges bounding the face.
+    pub fn face_edge(&self, f: FaceHandle) -> EdgeHandle {
+        self.faces[f.0].unwrap().edge
+    }
+
+    /// One of the half-edges starting at the vertex, or `None` if the vertex is isolated.
+    pub fn vertex_edge(&self, v: VertexHandle) -> Option<EdgeHandle> {
+        self.vertex(v).edge
+    }
+
+    /// The half-edge from `a` to `b`, if there is one.
+    pub fn find_edge(&self, a: VertexHandle, b: VertexHandle) -> Option<EdgeHandle> {
+        self.edge_map.get(&(a, b)).cloned()
+    }
+
+    /// The half-edges bounding the face, starting with `face_edge`.
+    pub fn face_edges(&self, f: FaceHandle) -> Vec<EdgeHandle> {
+        let start = self.face_edge(f);
+        let mut edges = vec!(start);
+        let mut e = self.next(start);
+
+        while e != start {
+            edges.push(e);
+            e = self.next(e);
+        }
+
+        edges
+    }
+
+    pub fn face_vertices(&self, f: FaceHandle) -> Vec<VertexHandle> {
+        self.face_edges(f).into_iter().map(|e| self.origin(e)).collect()
+    }
+
+    /// The half-edges starting at the vertex, in rotational order. For faces oriented
+    /// counter-clockwise the order is counter-clockwise as well. If the vertex is on the
+    /// boundary, the first edge is a boundary edge, and the boundary edge ending at the vertex
+    /// has no outgoing counterpart.
+    pub fn outgoing_edges(&self, v: VertexHandle) -> Vec<EdgeHandle> {
+        let start = match self.vertex_edge(v) {
+            Some(e) => e,
+            None => { return Vec::new(); }
+        };
+
+        let mut edges = vec!(start);
+        let mut e = start;
+
+        loop {
+            match self.twin(self.prev(e)) {
+                Some(n) if n == start => { return edges; },
+                Some(n) => { edges.push(n); e = n; },
+                None => { break; },
+            }
+        }
+
+        // Hit the boundary, so continue in the other direction from the start
+        let mut before = Vec::new();
+        e = start;
+
+        while let Some(t) = self.twin(e) {
+            e = self.next(t);
+            before.push(e);
+        }
+
+        before.reverse();
+        before.extend(edges);
+        before
+    }
+
+    fn vertex(&self, v: VertexHandle) -> &Vertex<P> {
+        self.vertices[v.0].as_ref().unwrap()
+    }
+
+    fn half_edge(&self, e: EdgeHandle) -> &HalfEdge {
+        self.edges[e.0].as_ref().unwrap()
+    }
+
+    fn half_edge_mut(&mut self, e: EdgeHandle) -> &mut HalfEdge {
+        self.edges[e.0].as_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> (Dcel<(f64, f64)>, Vec<VertexHandle>, FaceHandle, FaceHandle) {
+        let mut d = Dcel::new();
+        let v = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].iter().map(|&p| d.add_vertex(p)).collect::<Vec<_>>();
+        let f1 = d.add_face(&[v[0], v[1], v[2]]);
+        let f2 = d.add_face(&[v[0], v[2], v[3]]);
+        (d, v, f1, f2)
+    }
+
+    #[test]
+    fn dcel_navigation() {
+        let (d, v, f1, f2) = square();
+
+        let e = d.find_edge(v[0], v[2]).unwrap();
+        let t = d.twin(e).unwrap();
+
+        assert_eq!(d.face(e), f2);
+        assert_eq!(d.face(t), f1);
+        assert_eq!(d.origin(t), v[2]);
+        assert_eq!(d.destination(t), v[0]);
+        assert_eq!(d.origin(d.next(t)), v[0]);
+        assert_eq!(d.next(d.next(d.next(t))), t);
+        assert_eq!(d.prev(t), d.find_edge(v[1], v[2]).unwrap());
+        assert_eq!(d.face_vertices(f1), vec!(v[0], v[1], v[2]));
+        assert!(d.twin(d.find_edge(v[0], v[1]).unwrap()).is_none());
+    }
+
+    #[test]
+    fn dcel_outgoing_edges() {
+        let (d, v, _, _) = square();
+
+        let around = |x: VertexHandle| d.outgoing_edges(x).into_iter().map(|e| d.destination(e)).collect::<Vec<_>>();
+
+        assert_eq!(around(v[0]), vec!(v[1], v[2]));
+        assert_eq!(around(v[2]), vec!(v[3], v[0]));
+        assert_eq!(around(v[1]), vec!(v[2]));
+    }
+
+    #[test]
+    fn dcel_remove_face_reuses_handles() {
+        let (mut d, v, f1, f2) = square();
+
+        d.remove_face(f1);
+
+        assert_eq!(d.faces(), vec!(f2));
+        assert_eq!(d.edges().len(), 3);
+        assert!(d.twin(d.find_edge(v[0], v[2]).unwrap()).is_none());
+        assert!(d.vertex_edge(v[1]).is_none());
+
+        d.remove_vertex(v[1]);
+        let w = d.add_vertex((2.0, 0.0));
+        let f = d.add_face(&[v[0], w, v[2]]);
+
+        assert_eq!(w, v[1]);
+        assert_eq!(f, f1);
+        assert_eq!(d.edges().len(), 6);
+        assert_eq!(d.twin(d.find_edge(v[2], v[0]).unwrap()), d.find_edge(v[0], v[2]));
+    }
+}
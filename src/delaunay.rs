@@ -1,42 +1,828 @@
 use geometry::*;
 use in_circle::*;
+use error::GeometryError;
+use dcel::*;
+use random::XorShift;
 
+use std::collections::BTreeSet;
+use std::f64;
+
+/// The result of locating a point in a triangulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+    OnFace(FaceHandle),
+    /// On the edge, strictly between its end points.
+    OnEdge(EdgeHandle),
+    OnVertex(VertexHandle),
+    /// Outside of the domain.
+    Outside,
+}
+
+/// Quality bounds for `Delaunay::refine`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refinement {
+    /// The smallest allowed angle of a triangle, in degrees. Refinement is guaranteed to
+    /// terminate for bounds up to about 20.7 degrees if the input has no angles below 60
+    /// degrees. Otherwise `max_steiner_points` bounds the work.
+    pub min_angle: Option<f64>,
+    /// The largest allowed area of a triangle.
+    pub max_area: Option<f64>,
+    /// Stop after inserting this many Steiner points.
+    pub max_steiner_points: usize,
+}
+
+impl Default for Refinement {
+    fn default() -> Refinement {
+        Refinement { min_angle: Some(20.0), max_area: None, max_steiner_points: usize::MAX }
+    }
+}
+
+/// A Delaunay triangulation of points inside a domain triangle, stored in a half-edge structure.
+///
+/// The three vertices of the domain are part of the triangulation. They are not reported as
+/// sites.
+///
+/// Segments can be forced into the triangulation as constraints, which makes it a constrained
+/// Delaunay triangulation: constrained edges are never removed, and the Delaunay property only
+/// holds between points that can see each other across unconstrained edges.
+#[derive(Clone)]
 pub struct Delaunay<P> {
-    triangles: Vec<Triangle<P>>,
-    domain: Triangle<P>
+    dcel: Dcel<P>,
+    domain: Triangle<P>,
+    domain_vertices: [VertexHandle; 3],
+    /// Constrained edges, with the smaller vertex handle first. Ordered, so that refinement
+    /// processes them in the same order on every run.
+    constraints: BTreeSet<(VertexHandle, VertexHandle)>,
+    last_inserted: Option<VertexHandle>,
 }
 
-trait Triangulation<P> {
+pub trait Triangulation<P> {
     fn triangles(&self) -> Vec<Triangle<P>>;
 }
 
-impl<P> Triangulation<P> for Delaunay<P> where
-   Triangle<P>: Clone {
+impl<P: Copy> Triangulation<P> for Delaunay<P> {
     fn triangles(&self) -> Vec<Triangle<P>> {
-        self.triangles.clone()
+        self.dcel.faces().into_iter().map(|f| self.triangle(f)).collect()
     }
 }
 
-impl<P> Delaunay<P> where
-   Triangle<P>: Clone {
+impl<P: Copy> Delaunay<P> {
     pub fn new(t: Triangle<P>) -> Option<Delaunay<P>> {
-        Some(Delaunay { triangles: vec!(t.clone()), domain: t.clone() })
+        let mut dcel = Dcel::new();
+        let domain_vertices = [dcel.add_vertex(t.p1), dcel.add_vertex(t.p2), dcel.add_vertex(t.p3)];
+        dcel.add_face(&domain_vertices);
+
+        Some(Delaunay { dcel, domain: t, domain_vertices, constraints: BTreeSet::new(), last_inserted: None })
     }
 
     pub fn domain(&self) -> Triangle<P> {
-        self.domain.clone()
+        self.domain
+    }
+
+    /// The underlying half-edge structure.
+    pub fn dcel(&self) -> &Dcel<P> {
+        &self.dcel
+    }
+
+    /// The vertices of the inserted points, in ascending handle order.
+    pub fn vertices(&self) -> Vec<VertexHandle> {
+        self.dcel.vertices().into_iter().filter(|v| !self.is_domain_vertex(*v)).collect()
+    }
+
+    /// The points inserted so far, in the order of `vertices`.
+    pub fn sites(&self) -> Vec<P> {
+        self.vertices().into_iter().map(|v| self.dcel.point(v)).collect()
+    }
+
+    pub fn point(&self, v: VertexHandle) -> P {
+        self.dcel.point(v)
+    }
+
+    pub fn is_domain_vertex(&self, v: VertexHandle) -> bool {
+        self.domain_vertices.contains(&v)
+    }
+
+    pub fn triangle(&self, f: FaceHandle) -> Triangle<P> {
+        let v = self.dcel.face_vertices(f);
+        Triangle::new(self.dcel.point(v[0]), self.dcel.point(v[1]), self.dcel.point(v[2]))
+    }
+
+    /// The vertices connected to `v` by an edge, in rotational order.
+    pub fn neighbors(&self, v: VertexHandle) -> Vec<VertexHandle> {
+        let edges = self.dcel.outgoing_edges(v);
+        let mut neighbors = edges.iter().map(|&e| self.dcel.destination(e)).collect::<Vec<_>>();
+
+        // On the boundary of the domain the last neighbor is only reachable by an incoming edge
+        if let Some(&last) = edges.last() {
+            if self.dcel.twin(self.dcel.prev(last)).is_none() {
+                neighbors.push(self.dcel.origin(self.dcel.prev(last)));
+            }
+        }
+
+        neighbors
+    }
+
+    /// Returns true if the edge is part of a constraint.
+    pub fn is_constrained(&self, e: EdgeHandle) -> bool {
+        self.constraints.contains(&edge_key(self.dcel.origin(e), self.dcel.destination(e)))
+    }
+
+    /// The end points of all constrained edges. Constraints that were split by other
+    /// constraints or by inserted points are reported as their pieces.
+    pub fn constraints(&self) -> Vec<(VertexHandle, VertexHandle)> {
+        self.constraints.iter().cloned().collect()
+    }
+
+    /// A face incident to the vertex, a good hint to locate points close to it.
+    pub fn incident_face(&self, v: VertexHandle) -> Option<FaceHandle> {
+        self.dcel.vertex_edge(v).map(|e| self.dcel.face(e))
+    }
+}
+
+impl Delaunay<Point2D> {
+    /// Like `new`, but rejects domains that are degenerate or have invalid coordinates.
+    pub fn try_new(t: Triangle<Point2D>) -> Result<Delaunay<Point2D>, GeometryError> {
+        t.try_orientation()?;
+
+        Delaunay::new(t).ok_or(GeometryError::Degenerate)
+    }
+
+    /// Inserts a point using the Bowyer-Watson algorithm: all triangles whose circumcircle
+    /// contains the point are removed and the resulting cavity is re-triangulated by connecting
+    /// its boundary edges to the new point.
+    ///
+    /// Returns the vertex of the new site, or `None` if the point does not lie strictly inside
+    /// the domain or has already been inserted.
+    pub fn insert(&mut self, p: Point2D) -> Option<VertexHandle> {
+        let hint = self.last_inserted.and_then(|v| self.incident_face(v));
+        self.insert_with_hint(p, hint)
+    }
+
+    /// Like `insert`, but starts locating the point at the `hint` face.
+    pub fn insert_with_hint(&mut self, p: Point2D, hint: Option<FaceHandle>) -> Option<VertexHandle> {
+        self.try_insert_with_hint(p, hint).ok()
+    }
+
+    /// Like `insert`, but tells why the point was rejected.
+    pub fn try_insert(&mut self, p: Point2D) -> Result<VertexHandle, GeometryError> {
+        let hint = self.last_inserted.and_then(|v| self.incident_face(v));
+        self.try_insert_with_hint(p, hint)
+    }
+
+    /// Like `insert_with_hint`, but tells why the point was rejected.
+    pub fn try_insert_with_hint(&mut self, p: Point2D, hint: Option<FaceHandle>) -> Result<VertexHandle, GeometryError> {
+        let location = self.try_locate(&p, hint)?;
+        self.insert_at(p, location)
+    }
+
+    /// Inserts a point at a known location. A point on a constrained edge splits the constraint.
+    fn insert_at(&mut self, p: Point2D, location: Location) -> Result<VertexHandle, GeometryError> {
+        let (start, split) = match location {
+            Location::OnFace(f) => (vec!(f), None),
+            // Points on the boundary of the domain would create degenerate triangles
+            Location::OnEdge(e) => {
+                let t = self.dcel.twin(e).ok_or(GeometryError::OutsideDomain)?;
+                let split = Some((self.dcel.origin(e), self.dcel.destination(e))).filter(|_| self.is_constrained(e));
+                (vec!(self.dcel.face(e), self.dcel.face(t)), split)
+            },
+            Location::OnVertex(_) => { return Err(GeometryError::Duplicate); },
+            Location::Outside => { return Err(GeometryError::OutsideDomain); },
+        };
+
+        let cavity = self.cavity(&start, &p);
+
+        let boundary = cavity.iter().flat_map(|&f| self.dcel.face_edges(f)).filter(|&e| {
+            self.dcel.twin(e).is_none_or(|t| !cavity.contains(&self.dcel.face(t)))
+        }).map(|e| (self.dcel.origin(e), self.dcel.destination(e))).collect::<Vec<_>>();
+
+        // A point rounded to next to an edge, like the midpoint of a short constraint, may not
+        // see the whole boundary of its cavity
+        let orientation = self.domain.orientation();
+        if boundary.iter().any(|&(a, b)| Triangle::new(self.point(a), self.point(b), p).orientation() != orientation) {
+            return Err(GeometryError::Degenerate);
+        }
+
+        for &f in &cavity {
+            self.dcel.remove_face(f);
+        }
+
+        let v = self.dcel.add_vertex(p);
+
+        for (a, b) in boundary {
+            self.dcel.add_face(&[a, b, v]);
+        }
+
+        if let Some((a, b)) = split {
+            self.constraints.remove(&edge_key(a, b));
+            self.constraints.insert(edge_key(a, v));
+            self.constraints.insert(edge_key(v, b));
+
+            // A rounded split point can lie next to the constraint instead of on it, so the edges
+            // of its star may not be locally Delaunay
+            let star = self.dcel.outgoing_edges(v).into_iter().flat_map(|e| vec!(e, self.dcel.next(e)))
+                           .map(|e| (self.dcel.origin(e), self.dcel.destination(e))).collect();
+            self.legalize(star);
+        }
+
+        self.last_inserted = Some(v);
+
+        Ok(v)
+    }
+
+    /// Inserts a segment as a constraint. Its end points are inserted first, unless they are
+    /// already vertices of the triangulation. The triangles crossed by the segment are replaced by
+    /// a constrained Delaunay triangulation of the two polygons on either side of it.
+    ///
+    /// Vertices lying on the segment split it, and so do constrained edges crossing it. The
+    /// crossing constraint is split at the intersection point as well.
+    ///
+    /// Returns the vertices along the constraint from the first to the second end point, or
+    /// `None` if an end point is outside the domain, the segment is degenerate or it would have to
+    /// split a constraint on the boundary of the domain.
+    pub fn insert_constraint(&mut self, edge: Edge<Point2D>) -> Option<Vec<VertexHandle>> {
+        self.try_insert_constraint(edge).ok()
+    }
+
+    /// Like `insert_constraint`, but tells why the segment was rejected.
+    pub fn try_insert_constraint(&mut self, edge: Edge<Point2D>) -> Result<Vec<VertexHandle>, GeometryError> {
+        let a = self.vertex_at(edge.p1)?;
+        let b = self.vertex_at(edge.p2)?;
+
+        if a == b {
+            return Err(GeometryError::Degenerate);
+        }
+
+        self.recover_segment(a, b)
+    }
+
+    /// The vertex at `p`, inserting it if there is none yet.
+    fn vertex_at(&mut self, p: Point2D) -> Result<VertexHandle, GeometryError> {
+        match self.try_locate(&p, self.last_inserted.and_then(|v| self.incident_face(v)))? {
+            Location::OnVertex(v) => Ok(v),
+            location => self.insert_at(p, location),
+        }
+    }
+
+    fn recover_segment(&mut self, a: VertexHandle, b: VertexHandle) -> Result<Vec<VertexHandle>, GeometryError> {
+        if self.dcel.find_edge(a, b).is_some() || self.dcel.find_edge(b, a).is_some() {
+            self.constraints.insert(edge_key(a, b));
+            return Ok(vec!(a, b));
+        }
+
+        let (pa, pb) = (self.point(a), self.point(b));
+
+        // Find the face around `a` whose opposite edge is crossed by the segment, or a vertex
+        // lying on it.
+        let mut start = None;
+
+        for e in self.dcel.outgoing_edges(a) {
+            let crossing = self.dcel.next(e);
+            let (u, w) = (self.dcel.origin(crossing), self.dcel.destination(crossing));
+
+            for &x in &[u, w] {
+                if self.side(a, b, x).is_none() && is_ahead(&pa, &pb, &self.point(x)) {
+                    return self.split_segment(a, x, b);
+                }
+            }
+
+            if self.side(a, b, u).is_some() && self.side(a, b, w).is_some() && self.side(a, b, u) != self.side(a, b, w) &&
+               self.side(u, w, a).is_some() && self.side(u, w, b).is_some() && self.side(u, w, a) != self.side(u, w, b) {
+                start = Some(crossing);
+                break;
+            }
+        }
+
+        let mut crossing = start.ok_or(GeometryError::Degenerate)?;
+        let left_side = self.side(a, b, self.dcel.origin(crossing));
+        let mut faces = vec!(self.dcel.face(crossing));
+        let mut left = vec!(self.dcel.origin(crossing));
+        let mut right = vec!(self.dcel.destination(crossing));
+
+        loop {
+            if self.is_constrained(crossing) {
+                if self.dcel.twin(crossing).is_none() {
+                    return Err(GeometryError::BoundaryConstraint);
+                }
+
+                let (u, w) = (self.dcel.origin(crossing), self.dcel.destination(crossing));
+                let (pu, pw) = (self.point(u), self.point(w));
+                let x = Edge::new(pa, pb).intersection_point(&Edge::new(pu, pw)).ok_or(GeometryError::Overflow)?;
+
+                // The rounded intersection may coincide with one of the four end points. The
+                // constraint passing next to it is then routed through that vertex.
+                let tolerance = 4.0 * f64::EPSILON * (distance(&pa, &pb) + distance(&pu, &pw));
+                match [u, w, a, b].iter().cloned().find(|&v| distance(&self.point(v), &x) <= tolerance) {
+                    Some(v) if v == u || v == w => {
+                        return self.split_segment(a, v, b);
+                    },
+                    Some(v) => {
+                        self.constraints.remove(&edge_key(u, w));
+                        self.split_segment(u, v, w)?;
+
+                        // The old constraint, if it is still an edge, and the edges around the
+                        // vertex it now passes through may no longer be constrained Delaunay
+                        let mut edges = vec!((u, w));
+                        for e in self.dcel.outgoing_edges(v) {
+                            let opposite = self.dcel.next(e);
+                            edges.push((self.dcel.origin(e), self.dcel.destination(e)));
+                            edges.push((self.dcel.origin(opposite), self.dcel.destination(opposite)));
+                        }
+                        self.legalize(edges);
+
+                        return self.recover_segment(a, b);
+                    },
+                    None => {
+                        let v = self.insert_at(x, Location::OnEdge(crossing))?;
+                        return self.split_segment(a, v, b);
+                    },
+                }
+            }
+
+            // The segment lies inside the domain, so it only reaches the boundary through rounding
+            let twin = self.dcel.twin(crossing).ok_or(GeometryError::Degenerate)?;
+            faces.push(self.dcel.face(twin));
+
+            let x = self.dcel.destination(self.dcel.next(twin));
+
+            if x == b {
+                break;
+            }
+
+            let side = self.side(a, b, x);
+
+            if side.is_none() {
+                // The segment runs through `x`, so the part up to it can be recovered on its own
+                self.retriangulate_pipe(&faces, a, x, &left, &right);
+                self.constraints.insert(edge_key(a, x));
+
+                let mut chain = self.recover_segment(x, b)?;
+                chain.insert(0, a);
+                return Ok(chain);
+            }
+
+            // Continue across the edge of the new face that still separates the two sides. Its
+            // origin stays on the left side.
+            if side == left_side {
+                left.push(x);
+                crossing = self.dcel.next(self.dcel.next(twin));
+            } else {
+                right.push(x);
+                crossing = self.dcel.next(twin);
+            }
+        }
+
+        self.retriangulate_pipe(&faces, a, b, &left, &right);
+        self.constraints.insert(edge_key(a, b));
+
+        Ok(vec!(a, b))
+    }
+
+    /// Flips unconstrained edges whose opposite vertices lie inside each other's circumcircle,
+    /// until all edges reachable from the given ones are locally Delaunay.
+    fn legalize(&mut self, mut stack: Vec<(VertexHandle, VertexHandle)>) {
+        while let Some((a, b)) = stack.pop() {
+            let e = match self.dcel.find_edge(a, b) {
+                Some(e) if !self.is_constrained(e) => e,
+                _ => { continue; }
+            };
+            let t = match self.dcel.twin(e) {
+                Some(t) => t,
+                None => { continue; }
+            };
+
+            let c = self.dcel.destination(self.dcel.next(e));
+            let d = self.dcel.destination(self.dcel.next(t));
+
+            if self.triangle(self.dcel.face(e)).in_circle_test(&self.point(d)) != Some(InCircleLocation::Inside) {
+                continue;
+            }
+
+            self.dcel.remove_face(self.dcel.face(e));
+            self.dcel.remove_face(self.dcel.face(t));
+            self.add_oriented_face(a, d, c);
+            self.add_oriented_face(d, b, c);
+
+            stack.extend_from_slice(&[(a, c), (c, b), (b, d), (d, a)]);
+        }
+    }
+
+    fn split_segment(&mut self, a: VertexHandle, v: VertexHandle, b: VertexHandle) -> Result<Vec<VertexHandle>, GeometryError> {
+        let mut chain = self.recover_segment(a, v)?;
+        chain.pop();
+        chain.extend(self.recover_segment(v, b)?);
+        Ok(chain)
+    }
+
+    /// Replaces the faces crossed by the segment `a`-`b` by triangulations of the polygons on
+    /// both sides of it. `left` and `right` are the vertices of these polygons, in the order the
+    /// segment passes them.
+    fn retriangulate_pipe(&mut self, faces: &[FaceHandle], a: VertexHandle, b: VertexHandle,
+                          left: &[VertexHandle], right: &[VertexHandle]) {
+        for &f in faces {
+            self.dcel.remove_face(f);
+        }
+
+        self.fill_pseudo_polygon(a, b, left);
+        self.fill_pseudo_polygon(a, b, right);
+    }
+
+    /// Triangulates the polygon formed by the segment `a`-`b` and a chain of vertices on one side
+    /// of it: the vertex whose circle with `a` and `b` contains no other vertex of the chain forms
+    /// a triangle with them, and the polygons left and right of that triangle are filled the same
+    /// way.
+    fn fill_pseudo_polygon(&mut self, a: VertexHandle, b: VertexHandle, chain: &[VertexHandle]) {
+        if chain.is_empty() {
+            return;
+        }
+
+        let mut c = 0;
+        for i in 1..chain.len() {
+            let t = Triangle::new(self.point(a), self.point(b), self.point(chain[c]));
+            if t.in_circle_test(&self.point(chain[i])) == Some(InCircleLocation::Inside) {
+                c = i;
+            }
+        }
+
+        self.fill_pseudo_polygon(a, chain[c], &chain[..c]);
+        self.fill_pseudo_polygon(chain[c], b, &chain[c + 1..]);
+        self.add_oriented_face(a, b, chain[c]);
+    }
+
+    fn add_oriented_face(&mut self, a: VertexHandle, b: VertexHandle, c: VertexHandle) -> FaceHandle {
+        if self.side(a, b, c) == self.domain.orientation() {
+            self.dcel.add_face(&[a, b, c])
+        } else {
+            self.dcel.add_face(&[a, c, b])
+        }
+    }
+
+    fn side(&self, a: VertexHandle, b: VertexHandle, c: VertexHandle) -> Option<Orientation> {
+        Triangle::new(self.point(a), self.point(b), self.point(c)).orientation()
+    }
+
+    /// Refines the triangulation of the convex hull of the sites into a quality mesh with
+    /// Ruppert's algorithm, like the `-q` and `-a` switches of Triangle.
+    ///
+    /// The edges of the convex hull become constraints. Segments whose diametral circle contains
+    /// a vertex are split at their midpoint. Triangles violating the bounds get a Steiner point
+    /// at their circumcenter, unless that point would encroach a segment, which is then split
+    /// instead. Triangles outside the hull, connected to the domain vertices, are left alone.
+    ///
+    /// Returns the number of inserted Steiner points, or `None` if the convex hull could not be
+    /// recovered.
+    pub fn refine(&mut self, bounds: &Refinement) -> Option<usize> {
+        self.try_refine(bounds).ok()
+    }
+
+    /// Like `refine`, but tells why the convex hull could not be recovered.
+    pub fn try_refine(&mut self, bounds: &Refinement) -> Result<usize, GeometryError> {
+        self.constrain_hull()?;
+
+        let mut segments = self.constraints();
+        let mut faces = self.dcel.faces();
+        let mut inserted = 0;
+
+        while inserted < bounds.max_steiner_points {
+            // Encroached segments are split before any triangle is refined
+            if let Some((a, b)) = segments.pop() {
+                if self.constraints.contains(&edge_key(a, b)) && self.is_encroached(a, b, None) {
+                    if let Ok(v) = self.split_constraint(a, b) {
+                        inserted += 1;
+                        self.enqueue_star(v, &mut segments, &mut faces);
+                    }
+                }
+                continue;
+            }
+
+            let f = match faces.pop() {
+                Some(f) => f,
+                None => { break; }
+            };
+
+            if !self.dcel.contains_face(f) || self.is_outside_hull(f) || !is_bad(&self.triangle(f), bounds) {
+                continue;
+            }
+
+            let c = self.triangle(f).circumcenter();
+
+            let encroached = self.cavity(&[f], &c).into_iter().flat_map(|g| self.dcel.face_edges(g)).filter(|&e| {
+                self.is_constrained(e) && self.is_encroached(self.dcel.origin(e), self.dcel.destination(e), Some(&c))
+            }).map(|e| (self.dcel.origin(e), self.dcel.destination(e))).collect::<Vec<_>>();
+
+            if !encroached.is_empty() {
+                let mut split = false;
+                for (a, b) in encroached {
+                    if inserted < bounds.max_steiner_points && self.constraints.contains(&edge_key(a, b)) {
+                        if let Ok(v) = self.split_constraint(a, b) {
+                            inserted += 1;
+                            split = true;
+                            self.enqueue_star(v, &mut segments, &mut faces);
+                        }
+                    }
+                }
+
+                // The triangle may still be bad after the splits. If only constraints on the
+                // boundary of the domain are encroached, it is left as it is.
+                if split {
+                    faces.push(f);
+                }
+                continue;
+            }
+
+            let location = match self.locate(&c, Some(f)) {
+                Some(Location::OnFace(g)) if !self.is_outside_hull(g) => Location::OnFace(g),
+                Some(Location::OnEdge(e)) if !self.is_outside_hull(self.dcel.face(e)) => Location::OnEdge(e),
+                _ => { continue; }
+            };
+
+            if let Ok(v) = self.insert_at(c, location) {
+                inserted += 1;
+                self.enqueue_star(v, &mut segments, &mut faces);
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Adds the edges of the convex hull of the sites as constraints, computed with Andrew's
+    /// monotone chain.
+    pub(crate) fn constrain_hull(&mut self) -> Result<(), GeometryError> {
+        let mut sites = self.vertices();
+        sites.sort_by(|&a, &b| {
+            let (pa, pb) = (self.point(a), self.point(b));
+            (pa.x, pa.y).partial_cmp(&(pb.x, pb.y)).unwrap()
+        });
+
+        let mut hull: Vec<VertexHandle> = Vec::new();
+
+        for pass in 0..2 {
+            let start = hull.len();
+
+            for &v in &sites {
+                while hull.len() >= start + 2 && self.side(hull[hull.len() - 2], hull[hull.len() - 1], v) != Some(Orientation::Negative) {
+                    hull.pop();
+                }
+                hull.push(v);
+            }
+
+            // The upper chain runs back from right to left
+            if pass == 0 {
+                hull.pop();
+                sites.reverse();
+            }
+        }
+
+        for w in hull.windows(2) {
+            if w[0] != w[1] {
+                self.recover_segment(w[0], w[1])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if a face is outside the convex hull of the sites.
+    fn is_outside_hull(&self, f: FaceHandle) -> bool {
+        self.dcel.face_vertices(f).iter().any(|&v| self.is_domain_vertex(v))
+    }
+
+    /// Returns true if the diametral circle of the segment `a`-`b` strictly contains `p`, or if
+    /// no point is given, one of the sites opposite to the segment.
+    fn is_encroached(&self, a: VertexHandle, b: VertexHandle, p: Option<&Point2D>) -> bool {
+        let (pa, pb) = (self.point(a), self.point(b));
+        let encroaches = |p: &Point2D| (pa.x - p.x) * (pb.x - p.x) + (pa.y - p.y) * (pb.y - p.y) < 0.0;
+
+        match p {
+            Some(p) => encroaches(p),
+            None => [self.dcel.find_edge(a, b), self.dcel.find_edge(b, a)].iter().filter_map(|&e| e).any(|e| {
+                let apex = self.dcel.destination(self.dcel.next(e));
+                !self.is_domain_vertex(apex) && encroaches(&self.point(apex))
+            }),
+        }
+    }
+
+    /// Splits a constrained edge at its midpoint. Constraints on the boundary of the domain can't
+    /// be split.
+    fn split_constraint(&mut self, a: VertexHandle, b: VertexHandle) -> Result<VertexHandle, GeometryError> {
+        let e = self.dcel.find_edge(a, b).filter(|&e| self.dcel.twin(e).is_some())
+                    .or_else(|| self.dcel.find_edge(b, a).filter(|&e| self.dcel.twin(e).is_some()))
+                    .ok_or(GeometryError::BoundaryConstraint)?;
+        let (pa, pb) = (self.point(a), self.point(b));
+
+        self.insert_at(Point2D::new(0.5 * (pa.x + pb.x), 0.5 * (pa.y + pb.y)), Location::OnEdge(e))
+    }
+
+    /// Queues the faces around a new vertex and the segments it may encroach for refinement.
+    fn enqueue_star(&self, v: VertexHandle, segments: &mut Vec<(VertexHandle, VertexHandle)>, faces: &mut Vec<FaceHandle>) {
+        for e in self.dcel.outgoing_edges(v) {
+            faces.push(self.dcel.face(e));
+
+            for s in [e, self.dcel.next(e)] {
+                if self.is_constrained(s) {
+                    segments.push((self.dcel.origin(s), self.dcel.destination(s)));
+                }
+            }
+        }
+    }
+
+    /// Removes a site and re-triangulates the hole left by its star, keeping the Delaunay
+    /// property. The Voronoi diagram built from the triangulation afterwards no longer has a
+    /// cell for the site, and the cells of its former neighbors grow into its place.
+    ///
+    /// Returns the point of the removed site, or `None` if `v` is not a site of the
+    /// triangulation, is the end point of a constrained edge, or its hole cannot be
+    /// re-triangulated.
+    pub fn remove(&mut self, v: VertexHandle) -> Option<Point2D> {
+        self.try_remove(v).ok()
+    }
+
+    /// Like `remove`, but tells why the vertex could not be removed.
+    pub fn try_remove(&mut self, v: VertexHandle) -> Result<Point2D, GeometryError> {
+        if !self.dcel.contains_vertex(v) || self.is_domain_vertex(v) {
+            return Err(GeometryError::InvalidVertex);
+        }
+
+        if self.dcel.outgoing_edges(v).into_iter().any(|e| self.is_constrained(e)) {
+            return Err(GeometryError::Constrained);
+        }
+
+        let p = self.point(v);
+        let hole = self.hole_triangulation(self.neighbors(v)).ok_or(GeometryError::Degenerate)?;
+        let faces = self.dcel.outgoing_edges(v).into_iter().map(|e| self.dcel.face(e)).collect::<Vec<_>>();
+
+        for f in faces {
+            self.dcel.remove_face(f);
+        }
+
+        self.dcel.remove_vertex(v);
+
+        if self.last_inserted == Some(v) {
+            self.last_inserted = None;
+        }
+
+        for face in hole {
+            self.dcel.add_face(&face);
+        }
+
+        Ok(p)
+    }
+
+    /// Triangulates a star-shaped hole, given by its boundary in the orientation of the faces,
+    /// by repeatedly cutting off an ear whose circumcircle contains no other boundary vertex.
+    ///
+    /// Returns `None` if at some step no such ear exists.
+    fn hole_triangulation(&self, mut polygon: Vec<VertexHandle>) -> Option<Vec<Vec<VertexHandle>>> {
+        let orientation = self.domain.orientation();
+        let mut faces = Vec::new();
+
+        while polygon.len() > 3 {
+            let n = polygon.len();
+
+            let ear = (0..n).find(|&i| {
+                let (a, b, c) = (polygon[i], polygon[(i + 1) % n], polygon[(i + 2) % n]);
+                let t = Triangle::new(self.point(a), self.point(b), self.point(c));
+
+                t.orientation() == orientation && polygon.iter().all(|&w| {
+                    w == a || w == b || w == c || t.in_circle_test(&self.point(w)) != Some(InCircleLocation::Inside)
+                })
+            })?;
+
+            faces.push(vec![polygon[ear], polygon[(ear + 1) % n], polygon[(ear + 2) % n]]);
+            polygon.remove((ear + 1) % n);
+        }
+
+        faces.push(polygon);
+        Some(faces)
     }
+
+    /// Locates a point with a randomized visibility walk, starting at the `hint` face if given.
+    ///
+    /// From the current face the walk moves across an edge that separates the face from the
+    /// point, until there is none. The point is then classified with `Triangle::locate`.
+    /// Returns `None` for points with non-finite coordinates.
+    pub fn locate(&self, p: &Point2D, hint: Option<FaceHandle>) -> Option<Location> {
+        if !p.x.is_finite() || !p.y.is_finite() {
+            return None;
+        }
+
+        let orientation = self.domain.orientation()?;
+        let mut face = match hint {
+            Some(f) if self.dcel.contains_face(f) => f,
+            _ => self.dcel.face(self.dcel.vertex_edge(self.domain_vertices[0])?),
+        };
+
+        // Seeded by the query, so that locating a point has no side effects
+        let mut random = XorShift::new(p.x.to_bits() ^ p.y.to_bits().rotate_left(32) ^ face.index() as u64);
+
+        'walk: loop {
+            let edges = self.dcel.face_edges(face);
+            let offset = random.next() as usize;
+
+            for i in 0..edges.len() {
+                let e = edges[(i + offset) % edges.len()];
+                let side = Triangle::new(self.point(self.dcel.origin(e)), self.point(self.dcel.destination(e)), *p).orientation();
+
+                if side.is_some() && side.as_ref() != Some(&orientation) {
+                    match self.dcel.twin(e) {
+                        Some(t) => { face = self.dcel.face(t); continue 'walk; },
+                        None => { return Some(Location::Outside); },
+                    }
+                }
+            }
+
+            break;
+        }
+
+        if let Some(&v) = self.dcel.face_vertices(face).iter().find(|&&v| self.point(v) == *p) {
+            return Some(Location::OnVertex(v));
+        }
+
+        match self.triangle(face).locate(p)? {
+            TrianglePointLocation::OnEdge(edge) => {
+                self.dcel.face_edges(face).into_iter().find(|&e| {
+                    let (a, b) = (self.point(self.dcel.origin(e)), self.point(self.dcel.destination(e)));
+                    (a == edge.p1 && b == edge.p2) || (a == edge.p2 && b == edge.p1)
+                }).map(Location::OnEdge)
+            },
+            _ => Some(Location::OnFace(face)),
+        }
+    }
+
+    /// Like `locate`, but tells why the point could not be located.
+    pub fn try_locate(&self, p: &Point2D, hint: Option<FaceHandle>) -> Result<Location, GeometryError> {
+        p.validate()?;
+        self.domain.try_orientation()?;
+
+        self.locate(p, hint).ok_or(GeometryError::Degenerate)
+    }
+
+    /// The faces whose circumcircle strictly contains `p` and that are connected to the `start`
+    /// faces without crossing a constrained edge.
+    fn cavity(&self, start: &[FaceHandle], p: &Point2D) -> BTreeSet<FaceHandle> {
+        let mut cavity = start.iter().cloned().collect::<BTreeSet<_>>();
+        let mut visited = cavity.clone();
+        let mut stack = start.to_vec();
+
+        while let Some(f) = stack.pop() {
+            for e in self.dcel.face_edges(f) {
+                let t = match self.dcel.twin(e) {
+                    Some(t) if !self.is_constrained(e) => t,
+                    _ => { continue; }
+                };
+
+                let g = self.dcel.face(t);
+                if visited.insert(g) && self.triangle(g).in_circle_test(p) == Some(InCircleLocation::Inside) {
+                    cavity.insert(g);
+                    stack.push(g);
+                }
+            }
+        }
+
+        cavity
+    }
+}
+
+/// Returns true if the triangle violates the quality bounds. The smallest angle is compared via
+/// the ratio of the circumradius to the shortest edge, which is `1 / (2 sin(angle))`.
+fn is_bad(t: &Triangle<Point2D>, bounds: &Refinement) -> bool {
+    if bounds.max_area.is_some_and(|max| t.area() > max) {
+        return true;
+    }
+
+    bounds.min_angle.is_some_and(|angle| {
+        let shortest = t.edges().iter().map(|e| distance(&e.p1, &e.p2)).fold(f64::INFINITY, f64::min);
+        let radius = distance(&t.circumcenter(), &t.p1);
+
+        radius > shortest / (2.0 * angle.to_radians().sin())
+    })
+}
+
+fn edge_key(a: VertexHandle, b: VertexHandle) -> (VertexHandle, VertexHandle) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// Returns true if `p` lies on the same side of `a` as `b`, for `p` on the line through `a` and
+/// `b`.
+fn is_ahead(a: &Point2D, b: &Point2D, p: &Point2D) -> bool {
+    (p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y) > 0.0
+}
+
+fn distance(a: &Point2D, b: &Point2D) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use geometry::*;
-    use in_circle::*;
 
     use delaunay::Triangulation;
 
-    use quickcheck::{TestResult, quickcheck, QuickCheck, StdGen};
+    use std::f64;
+    use quickcheck::{TestResult, quickcheck};
+
+    fn domain() -> Triangle<Point2D> {
+        Triangle::new(Point2D::new(-100.0, -100.0), Point2D::new(100.0, -100.0), Point2D::new(0.0, 100.0))
+    }
 
     #[test]
     fn new_delaunay_test() {
@@ -53,4 +839,406 @@ mod tests {
         }
         quickcheck(new_delaunay_test as fn(pnt1: (f64, f64), pnt2: (f64, f64), pnt3: (f64, f64)) -> TestResult)
     }
+
+    #[test]
+    fn insert_single_point() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let p = Point2D::new(0.0, 0.0);
+
+        let v = d.insert(p).unwrap();
+
+        assert_eq!(d.point(v), p);
+        assert_eq!(d.vertices(), vec!(v));
+        assert_eq!(d.sites(), vec!(p));
+        assert_eq!(d.triangles().len(), 3);
+        assert!(d.triangles().iter().all(|t| t.edges().iter().any(|e| e.p2 == p || e.p1 == p)));
+        assert_eq!(d.neighbors(v).len(), 3);
+    }
+
+    #[test]
+    fn insert_rejects_outside_and_duplicate_points() {
+        let mut d = Delaunay::new(domain()).unwrap();
+
+        assert_eq!(d.insert(Point2D::new(500.0, 0.0)), None);
+        assert!(d.insert(Point2D::new(1.0, 1.0)).is_some());
+        assert_eq!(d.insert(Point2D::new(1.0, 1.0)), None);
+        assert_eq!(d.triangles().len(), 3);
+    }
+
+    #[test]
+    fn neighbors_of_domain_vertex() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let v = d.insert(Point2D::new(0.0, 0.0)).unwrap();
+
+        let corner = d.dcel().vertices()[0];
+        let mut neighbors = d.neighbors(corner);
+        neighbors.sort();
+
+        assert_eq!(neighbors, vec!(d.dcel().vertices()[1], d.dcel().vertices()[2], v));
+    }
+
+    #[test]
+    fn locate_classifies_points() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let a = d.insert(Point2D::new(0.0, 0.0)).unwrap();
+        let b = d.insert(Point2D::new(10.0, 0.0)).unwrap();
+        d.insert(Point2D::new(0.0, 10.0)).unwrap();
+
+        assert_eq!(d.locate(&Point2D::new(0.0, 0.0), None), Some(Location::OnVertex(a)));
+        assert_eq!(d.locate(&Point2D::new(500.0, 0.0), None), Some(Location::Outside));
+        assert_eq!(d.locate(&Point2D::new(0.0, f64::NAN), None), None);
+
+        match d.locate(&Point2D::new(5.0, 0.0), None) {
+            Some(Location::OnEdge(e)) => {
+                let mut ends = [d.dcel().origin(e), d.dcel().destination(e)];
+                ends.sort();
+                assert_eq!(ends, [a, b]);
+            },
+            l => panic!("unexpected location {:?}", l),
+        }
+
+        match d.locate(&Point2D::new(1.0, 1.0), None) {
+            Some(Location::OnFace(f)) => {
+                assert_eq!(d.triangle(f).locate(&Point2D::new(1.0, 1.0)), Some(TrianglePointLocation::Inside));
+            },
+            l => panic!("unexpected location {:?}", l),
+        }
+    }
+
+    #[test]
+    fn locate_finds_containing_face() {
+        fn locate_finds_containing_face(points: Vec<(f64, f64)>, query: (f64, f64), hint: usize) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in &points {
+                d.insert(Point2D::new(x % 50.0, y % 50.0));
+            }
+
+            let p = Point2D::new(query.0 % 50.0, query.1 % 50.0);
+            let faces = d.dcel().faces();
+            let hint = faces[hint % faces.len()];
+
+            TestResult::from_bool(match d.locate(&p, Some(hint)) {
+                Some(Location::OnFace(f)) => d.triangle(f).locate(&p) == Some(TrianglePointLocation::Inside),
+                Some(Location::OnEdge(e)) => d.triangle(d.dcel().face(e)).locate(&p) != Some(TrianglePointLocation::Inside),
+                Some(Location::OnVertex(v)) => d.point(v) == p,
+                Some(Location::Outside) => d.domain().locate(&p) == Some(TrianglePointLocation::Outside),
+                None => false,
+            })
+        }
+        quickcheck(locate_finds_containing_face as fn(points: Vec<(f64, f64)>, query: (f64, f64), hint: usize) -> TestResult)
+    }
+
+    #[test]
+    fn try_versions_report_errors() {
+        let collinear = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0));
+        assert_eq!(Delaunay::try_new(collinear).err(), Some(GeometryError::Degenerate));
+
+        let mut d = Delaunay::try_new(domain()).unwrap();
+        let v = d.try_insert(Point2D::new(0.0, 0.0)).unwrap();
+        let w = d.try_insert(Point2D::new(10.0, 0.0)).unwrap();
+
+        assert_eq!(d.try_insert(Point2D::new(0.0, 0.0)), Err(GeometryError::Duplicate));
+        assert_eq!(d.try_insert(Point2D::new(500.0, 0.0)), Err(GeometryError::OutsideDomain));
+        assert_eq!(d.try_insert(Point2D::new(0.0, -100.0)), Err(GeometryError::OutsideDomain));
+        assert_eq!(d.try_insert(Point2D::new(f64::NAN, 0.0)), Err(GeometryError::NotANumber));
+        assert_eq!(d.try_locate(&Point2D::new(0.0, f64::INFINITY), None), Err(GeometryError::Infinite));
+
+        assert_eq!(d.try_insert_constraint(Edge::new(Point2D::new(0.0, 0.0), Point2D::new(0.0, 0.0))), Err(GeometryError::Degenerate));
+        assert_eq!(d.try_insert_constraint(Edge::new(Point2D::new(0.0, 0.0), Point2D::new(0.0, 200.0))), Err(GeometryError::OutsideDomain));
+        assert_eq!(d.try_insert_constraint(Edge::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0))), Ok(vec!(v, w)));
+
+        assert_eq!(d.try_remove(v), Err(GeometryError::Constrained));
+        assert_eq!(d.try_remove(d.dcel().vertices()[0]), Err(GeometryError::InvalidVertex));
+    }
+
+    #[test]
+    fn remove_restores_previous_triangulation() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        d.insert(Point2D::new(0.0, 0.0)).unwrap();
+        d.insert(Point2D::new(20.0, 0.0)).unwrap();
+        let before = d.triangles();
+
+        let v = d.insert(Point2D::new(10.0, 1.0)).unwrap();
+        assert_eq!(d.remove(v), Some(Point2D::new(10.0, 1.0)));
+        assert_eq!(d.remove(v), None);
+        assert_eq!(d.remove(d.dcel().vertices()[0]), None);
+
+        let mut after = d.triangles();
+        assert_eq!(after.len(), before.len());
+        after.retain(|t| !before.iter().any(|b| [t.p1, t.p2, t.p3].iter().all(|p| [b.p1, b.p2, b.p3].contains(p))));
+        assert!(after.is_empty());
+    }
+
+    #[test]
+    fn remove_keeps_delaunay_property() {
+        fn remove_keeps_delaunay_property(points: Vec<(f64, f64)>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            let vertices = points.iter().filter_map(|&(x, y)| d.insert(Point2D::new(x % 50.0, y % 50.0))).collect::<Vec<_>>();
+
+            for v in vertices.iter().step_by(2) {
+                d.remove(*v);
+            }
+
+            let triangles = d.triangles();
+            let sites = d.sites();
+
+            let euler = triangles.len() == 2 * sites.len() + 1;
+            let empty_circles = triangles.iter().all(|t| {
+                sites.iter().all(|p| t.in_circle_test(p) != Some(InCircleLocation::Inside))
+            });
+
+            TestResult::from_bool(euler && empty_circles && sites.len() == vertices.len() / 2)
+        }
+        quickcheck(remove_keeps_delaunay_property as fn(points: Vec<(f64, f64)>) -> TestResult)
+    }
+
+    #[test]
+    fn insert_keeps_delaunay_property() {
+        fn insert_keeps_delaunay_property(points: Vec<(f64, f64)>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            let points = points.iter().map(|&(x, y)| Point2D::new(x % 50.0, y % 50.0)).collect::<Vec<_>>();
+
+            for p in &points {
+                d.insert(*p);
+            }
+
+            let triangles = d.triangles();
+            let sites = d.sites();
+
+            // Euler: a triangulation of n interior points in a triangle has 2n + 1 faces
+            let euler = triangles.len() == 2 * sites.len() + 1;
+            let empty_circles = triangles.iter().all(|t| {
+                sites.iter().all(|p| t.in_circle_test(p) != Some(InCircleLocation::Inside))
+            });
+            // Every interior vertex has as many neighbors as incident faces
+            let connected = d.vertices().iter().all(|&v| {
+                d.neighbors(v).len() == d.dcel().outgoing_edges(v).len()
+            });
+
+            TestResult::from_bool(euler && empty_circles && connected)
+        }
+        quickcheck(insert_keeps_delaunay_property as fn(points: Vec<(f64, f64)>) -> TestResult)
+    }
+
+    fn constrained_edges_exist(d: &Delaunay<Point2D>) -> bool {
+        d.constraints().iter().all(|&(a, b)| d.dcel().find_edge(a, b).is_some() || d.dcel().find_edge(b, a).is_some())
+    }
+
+    /// Checks that every unconstrained edge is locally Delaunay, and that the constraints are
+    /// edges of a valid triangulation.
+    fn is_constrained_delaunay(d: &Delaunay<Point2D>) -> bool {
+        let dcel = d.dcel();
+
+        let locally_delaunay = dcel.edges().into_iter().all(|e| {
+            d.is_constrained(e) || dcel.twin(e).is_none_or(|t| {
+                let opposite = d.point(dcel.origin(dcel.prev(t)));
+                d.triangle(dcel.face(e)).in_circle_test(&opposite) != Some(InCircleLocation::Inside)
+            })
+        });
+        let oriented = d.triangles().iter().all(|t| t.orientation() == d.domain().orientation());
+
+        locally_delaunay && oriented && constrained_edges_exist(d) && d.triangles().len() == 2 * d.sites().len() + 1
+    }
+
+    #[test]
+    fn insert_constraint_recovers_segment() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 2.0), (0.0, -2.0), (5.0, 1.0), (-5.0, -1.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+
+        let chain = d.insert_constraint(Edge::new(Point2D::new(-20.0, 0.0), Point2D::new(20.0, 0.0))).unwrap();
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!((d.point(chain[0]), d.point(chain[1])), (Point2D::new(-20.0, 0.0), Point2D::new(20.0, 0.0)));
+        assert_eq!(d.constraints().len(), 1);
+        assert!(constrained_edges_exist(&d));
+        assert_eq!(d.triangles().len(), 2 * d.sites().len() + 1);
+
+        // Constrained edges are kept by later insertions, and their end points can't be removed
+        d.insert(Point2D::new(0.0, 0.5));
+        assert!(constrained_edges_exist(&d));
+        assert_eq!(d.remove(chain[0]), None);
+        assert_eq!(d.insert_constraint(Edge::new(Point2D::new(1.0, 1.0), Point2D::new(1.0, 1.0))), None);
+        assert_eq!(d.insert_constraint(Edge::new(Point2D::new(1.0, 1.0), Point2D::new(500.0, 1.0))), None);
+    }
+
+    #[test]
+    fn insert_constraint_splits_at_vertices_and_crossings() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let center = d.insert(Point2D::new(0.0, 0.0)).unwrap();
+
+        let chain = d.insert_constraint(Edge::new(Point2D::new(-20.0, 0.0), Point2D::new(20.0, 0.0))).unwrap();
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[1], center);
+
+        let chain = d.insert_constraint(Edge::new(Point2D::new(10.0, -10.0), Point2D::new(10.0, 10.0))).unwrap();
+        assert_eq!(chain.len(), 3);
+        assert_eq!(d.point(chain[1]), Point2D::new(10.0, 0.0));
+        assert_eq!(d.constraints().len(), 5);
+        assert!(constrained_edges_exist(&d));
+
+        // Inserting a point on a constrained edge splits it
+        d.insert(Point2D::new(-10.0, 0.0)).unwrap();
+        assert_eq!(d.constraints().len(), 6);
+        assert!(constrained_edges_exist(&d));
+        assert_eq!(d.triangles().len(), 2 * d.sites().len() + 1);
+    }
+
+    #[test]
+    fn insert_constraint_through_vertex_next_to_constraint() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(-10.0, 3.0), (10.0, 3.0), (-10.0, -3.0), (10.0, -3.0), (0.0, 6.0), (0.0, -6.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+        let u = d.insert(Point2D::new(-20.0, 0.0)).unwrap();
+        let w = d.insert(Point2D::new(20.0, 0.0)).unwrap();
+        d.insert_constraint(Edge::new(d.point(u), d.point(w))).unwrap();
+
+        // The vertex is closer to the constraint than the rounding error of the crossing, so the
+        // constraint is routed through it instead of being split next to it
+        let chain = d.insert_constraint(Edge::new(Point2D::new(0.0, 1e-14), Point2D::new(0.0, -5.0))).unwrap();
+        let v = chain[0];
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!(d.sites().len(), 10);
+        assert!(d.constraints().contains(&edge_key(u, v)) && d.constraints().contains(&edge_key(v, w)));
+        assert!(!d.constraints().contains(&edge_key(u, w)));
+        assert!(is_constrained_delaunay(&d));
+    }
+
+    #[test]
+    fn insert_constraint_on_the_domain_boundary() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let v = d.insert(Point2D::new(0.0, 0.0)).unwrap();
+
+        assert_eq!(d.try_insert_constraint(Edge::new(Point2D::new(0.0, -100.0), Point2D::new(0.0, 0.0))), Err(GeometryError::OutsideDomain));
+        assert_eq!(d.try_insert_constraint(Edge::new(Point2D::new(0.0, 0.0), Point2D::new(40.0, 0.0))).map(|c| c[0]), Ok(v));
+
+        // Edges of the domain and segments to its vertices can be constraints
+        let t = d.domain();
+        assert_eq!(d.try_insert_constraint(Edge::new(t.p1, t.p2)).map(|c| c.len()), Ok(2));
+        assert_eq!(d.try_insert_constraint(Edge::new(t.p3, Point2D::new(10.0, -10.0))).map(|c| c.len()), Ok(3));
+        assert_eq!(d.constraints().len(), 5);
+        assert!(is_constrained_delaunay(&d));
+    }
+
+    #[test]
+    fn insert_constraint_keeps_constrained_delaunay_property() {
+        fn insert_constraint_keeps_constrained_delaunay_property(points: Vec<(f64, f64)>, segments: Vec<((f64, f64), (f64, f64))>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in &points {
+                d.insert(Point2D::new(x % 50.0, y % 50.0));
+            }
+
+            // Keep the coordinates on a coarse grid, so that crossings are exactly representable
+            let grid = |x: f64| (x % 50.0).round();
+            for &((x1, y1), (x2, y2)) in segments.iter().take(5) {
+                d.insert_constraint(Edge::new(Point2D::new(grid(x1), grid(y1)), Point2D::new(grid(x2), grid(y2))));
+            }
+
+            TestResult::from_bool(is_constrained_delaunay(&d))
+        }
+        quickcheck(insert_constraint_keeps_constrained_delaunay_property as fn(points: Vec<(f64, f64)>, segments: Vec<((f64, f64), (f64, f64))>) -> TestResult)
+    }
+
+    fn smallest_angle(t: &Triangle<Point2D>) -> f64 {
+        let angle = |a: &Point2D, b: &Point2D, c: &Point2D| {
+            let (u, v) = ((b.x - a.x, b.y - a.y), (c.x - a.x, c.y - a.y));
+            (u.0 * v.1 - u.1 * v.0).abs().atan2(u.0 * v.0 + u.1 * v.1).to_degrees()
+        };
+
+        angle(&t.p1, &t.p2, &t.p3).min(angle(&t.p2, &t.p3, &t.p1)).min(angle(&t.p3, &t.p1, &t.p2))
+    }
+
+    fn mesh(d: &Delaunay<Point2D>) -> Vec<Triangle<Point2D>> {
+        d.dcel().faces().into_iter().filter(|&f| !d.dcel().face_vertices(f).iter().any(|&v| d.is_domain_vertex(v)))
+                        .map(|f| d.triangle(f)).collect()
+    }
+
+    #[test]
+    fn refine_meets_quality_bounds() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (40.0, 0.0), (40.0, 1.0), (0.0, 30.0), (20.0, 29.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+        d.insert_constraint(Edge::new(Point2D::new(0.0, 0.0), Point2D::new(20.0, 29.0))).unwrap();
+
+        let bounds = Refinement { min_angle: Some(20.0), max_area: Some(50.0), ..Refinement::default() };
+        let inserted = d.refine(&bounds).unwrap();
+
+        let triangles = mesh(&d);
+        assert!(inserted > 0);
+        assert_eq!(d.sites().len(), 5 + inserted);
+        assert!(triangles.iter().all(|t| smallest_angle(t) >= 20.0 - 1e-9 && t.area() <= 50.0));
+        assert!(constrained_edges_exist(&d));
+
+        // The mesh still covers the convex hull of the input
+        let area = triangles.iter().map(|t| t.area()).sum::<f64>();
+        assert!((area - 890.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn refine_stops_after_max_steiner_points() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (40.0, 0.0), (20.0, 30.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+
+        let bounds = Refinement { max_area: Some(10.0), ..Refinement::default() };
+
+        assert_eq!(d.refine(&Refinement { max_steiner_points: 3, ..bounds }), Some(3));
+        assert_eq!(d.sites().len(), 6);
+        assert!(d.refine(&bounds).unwrap() > 0);
+        assert!(mesh(&d).iter().all(|t| smallest_angle(t) >= 20.0 - 1e-9 && t.area() <= 10.0));
+    }
+
+    #[test]
+    fn refine_keeps_constraints_on_the_domain_boundary() {
+        let sites = [(-5.0, -40.0), (-8.0, 41.0), (-41.0, -13.0), (-36.0, -48.0), (25.0, -4.0), (-17.0, 29.0)];
+        let spokes = [(-20.0, -42.0), (30.0, 20.0), (-46.0, -31.0)];
+
+        // The insertion order decides the handles, and with them the order in which segments and
+        // triangles are refined
+        for shift in 0..sites.len() {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in sites.iter().cycle().skip(shift).take(sites.len()) {
+                d.insert(Point2D::new(x, y));
+            }
+
+            // The boundary edge is encroached by the sites next to it, but can't be split
+            let t = d.domain();
+            let boundary = d.insert_constraint(Edge::new(t.p1, t.p2)).unwrap();
+            assert_eq!(d.split_constraint(boundary[0], boundary[1]), Err(GeometryError::BoundaryConstraint));
+            for &(x, y) in spokes.iter().cycle().skip(shift).take(spokes.len()) {
+                d.insert_constraint(Edge::new(Point2D::new(x, y), t.p2)).unwrap();
+            }
+
+            assert!(d.refine(&Refinement { max_steiner_points: 200, ..Refinement::default() }).unwrap() > 0);
+            assert!(d.constraints().contains(&edge_key(boundary[0], boundary[1])));
+            assert!(is_constrained_delaunay(&d));
+        }
+    }
+
+    #[test]
+    fn refine_keeps_constrained_delaunay_property() {
+        fn refine_keeps_constrained_delaunay_property(points: Vec<(f64, f64)>, spokes: Vec<(f64, f64)>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in &points {
+                d.insert(Point2D::new(x % 50.0, y % 50.0));
+            }
+
+            // Segments to a corner of the domain form small angles, which refinement can't fix
+            let t = d.domain();
+            d.insert_constraint(Edge::new(t.p1, t.p2));
+            for &(x, y) in spokes.iter().take(3) {
+                d.insert_constraint(Edge::new(Point2D::new(x % 50.0, y % 50.0), t.p2));
+            }
+
+            d.refine(&Refinement { max_steiner_points: 100, ..Refinement::default() });
+
+            TestResult::from_bool(is_constrained_delaunay(&d))
+        }
+        quickcheck(refine_keeps_constrained_delaunay_property as fn(points: Vec<(f64, f64)>, spokes: Vec<(f64, f64)>) -> TestResult)
+    }
 }
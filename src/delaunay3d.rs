@@ -0,0 +1,590 @@
+use geometry::*;
+use in_circle::*;
+use expansion::Expansion;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// A Delaunay tetrahedralization of points inside a domain tetrahedron.
+///
+/// Vertices are referenced by index. The four vertices of the domain come first, the inserted
+/// sites follow in insertion order. Each tetrahedron keeps the orientation of the domain.
+pub struct Delaunay3D {
+    vertices: Vec<Point3D>,
+    tetrahedra: Vec<[usize; 4]>,
+    neighbors: Vec<[Option<usize>; 4]>,
+    domain: Tetrahedron<Point3D>,
+    orientation: Orientation,
+    last_created: usize,
+    /// Whether the domain is scaled to infinity, see `unbounded`.
+    at_infinity: bool,
+}
+
+impl Delaunay3D {
+    /// Returns `None` if the domain is degenerate.
+    pub fn new(t: Tetrahedron<Point3D>) -> Option<Delaunay3D> {
+        let orientation = t.orientation()?;
+
+        Some(Delaunay3D {
+            vertices: vec!(t.p1, t.p2, t.p3, t.p4),
+            tetrahedra: vec!([0, 1, 2, 3]),
+            neighbors: vec!([None; 4]),
+            domain: t,
+            orientation,
+            last_created: 0,
+            at_infinity: false,
+        })
+    }
+
+    /// A tetrahedralization whose domain is scaled to infinity, so that it contains every point
+    /// and no vertex of the domain lies inside the circumsphere of a tetrahedron of the sites.
+    /// The vertices of the domain are stored as their directions, and the predicates involving
+    /// them are evaluated as polynomials in the scale.
+    pub(crate) fn unbounded() -> Delaunay3D {
+        let directions = Tetrahedron::new(Point3D::new(1.0, 1.0, 1.0),
+                                          Point3D::new(1.0, -1.0, -1.0),
+                                          Point3D::new(-1.0, 1.0, -1.0),
+                                          Point3D::new(-1.0, -1.0, 1.0));
+
+        let mut d = Delaunay3D::new(directions).unwrap();
+        d.at_infinity = true;
+        d
+    }
+
+    pub fn domain(&self) -> Tetrahedron<Point3D> {
+        self.domain
+    }
+
+    /// All vertices, starting with the four vertices of the domain.
+    pub fn vertices(&self) -> &[Point3D] {
+        &self.vertices
+    }
+
+    /// The points inserted so far, in insertion order.
+    pub fn sites(&self) -> &[Point3D] {
+        &self.vertices[4..]
+    }
+
+    /// The vertex indices of each tetrahedron.
+    pub fn tetrahedron_indices(&self) -> &[[usize; 4]] {
+        &self.tetrahedra
+    }
+
+    pub fn tetrahedra(&self) -> Vec<Tetrahedron<Point3D>> {
+        self.tetrahedra.iter().map(|&t| self.tetrahedron(t)).collect()
+    }
+
+    /// Every face of the tetrahedralization, once.
+    pub fn faces(&self) -> Vec<Triangle<Point3D>> {
+        self.tetrahedra.iter().enumerate().flat_map(|(i, t)| {
+            (0..4).filter(move |&k| self.neighbors[i][k].is_none_or(|n| n > i))
+                  .map(move |k| face(t, k))
+        }).map(|f| Triangle::new(self.vertices[f[0]], self.vertices[f[1]], self.vertices[f[2]])).collect()
+    }
+
+    /// The neighbors of a tetrahedron. Entry `k` is the tetrahedron sharing the face opposite to
+    /// its `k`-th vertex, or `None` on the boundary of the domain.
+    pub fn neighbors(&self, tetrahedron: usize) -> [Option<usize>; 4] {
+        self.neighbors[tetrahedron]
+    }
+
+    /// Inserts a point using the Bowyer-Watson algorithm: all tetrahedra whose circumsphere
+    /// contains the point are removed and each boundary face of the cavity is connected to the
+    /// new point.
+    ///
+    /// Returns the index of the new vertex, or `None` if the point does not lie strictly inside
+    /// the domain or has already been inserted.
+    pub fn insert(&mut self, p: Point3D) -> Option<usize> {
+        if self.at_infinity {
+            p.validate().ok()?;
+        } else {
+            match self.domain.locate(&p) {
+                Some(TetrahedronPointLocation::Inside) => {},
+                _ => { return None; }
+            };
+        }
+
+        let index = self.vertices.len();
+        self.vertices.push(p);
+
+        // The circumsphere of the tetrahedron containing the point only misses it if the point
+        // is one of its vertices
+        let start = self.locate(index);
+        if self.in_sphere(self.tetrahedra[start], index) != Some(InCircleLocation::Inside) {
+            self.vertices.pop();
+            return None;
+        }
+
+        let cavity = self.cavity(start, index);
+        let in_cavity = cavity.iter().cloned().collect::<HashSet<_>>();
+
+        // Replacing the vertex opposite to a boundary face by the new point keeps the
+        // orientation, as the cavity is star-shaped with respect to it.
+        let mut created = Vec::new();
+        for &c in &cavity {
+            for k in 0..4 {
+                let outside = self.neighbors[c][k];
+                if outside.is_none_or(|n| !in_cavity.contains(&n)) {
+                    let mut t = self.tetrahedra[c];
+                    t[k] = index;
+                    created.push((t, k, outside));
+                }
+            }
+        }
+
+        // The new tetrahedra take the places of the removed ones first. Only the faces opposite
+        // to the new point are shared with tetrahedra outside the cavity, the others are matched
+        // up among the new tetrahedra.
+        let slots = cavity.iter().cloned().chain(self.tetrahedra.len()..).take(created.len()).collect::<Vec<_>>();
+        let mut faces: HashMap<[usize; 3], (usize, usize)> = HashMap::new();
+
+        for (&i, &(t, k, outside)) in slots.iter().zip(&created) {
+            if i == self.tetrahedra.len() {
+                self.tetrahedra.push(t);
+                self.neighbors.push([None; 4]);
+            } else {
+                self.tetrahedra[i] = t;
+                self.neighbors[i] = [None; 4];
+            }
+
+            self.neighbors[i][k] = outside;
+            if let Some(n) = outside {
+                let l = (0..4).find(|&l| !t.contains(&self.tetrahedra[n][l])).unwrap();
+                self.neighbors[n][l] = Some(i);
+            }
+
+            for l in (0..4).filter(|&l| l != k) {
+                let mut key = face(&t, l);
+                key.sort();
+
+                if let Some((j, m)) = faces.remove(&key) {
+                    self.neighbors[i][l] = Some(j);
+                    self.neighbors[j][m] = Some(i);
+                } else {
+                    faces.insert(key, (i, l));
+                }
+            }
+        }
+
+        self.last_created = slots[0];
+
+        // A cavity can have more tetrahedra than faces on its boundary
+        let mut unused = cavity[slots.len().min(cavity.len())..].to_vec();
+        unused.sort_by(|a, b| b.cmp(a));
+        for i in unused {
+            self.remove_tetrahedron(i);
+        }
+
+        Some(index)
+    }
+
+    fn tetrahedron(&self, t: [usize; 4]) -> Tetrahedron<Point3D> {
+        Tetrahedron::new(self.vertices[t[0]], self.vertices[t[1]], self.vertices[t[2]], self.vertices[t[3]])
+    }
+
+    fn is_finite(&self, t: [usize; 4]) -> bool {
+        !self.at_infinity || t.iter().all(|&v| v >= 4)
+    }
+
+    /// The coordinates of a vertex as polynomials in the scale of the domain.
+    fn coordinates<C: Coefficient>(&self, v: usize) -> Vec<Polynomial<C>> {
+        let p = self.vertices[v];
+        let scaled = self.at_infinity && v < 4;
+
+        [p.x, p.y, p.z].iter().map(|&x| {
+            if scaled { vec!(C::from(0.0), C::from(x)) } else { vec!(C::from(x)) }
+        }).collect()
+    }
+
+    /// The matrix of orient3d, with a column of ones instead of the differences to the last
+    /// vertex. Two differences to a vertex at infinity would cancel out in the leading terms.
+    fn orientation_matrix<C: Coefficient>(&self, t: [usize; 4]) -> Vec<Vec<Polynomial<C>>> {
+        t.iter().map(|&v| {
+            let mut row = self.coordinates(v);
+            row.push(vec!(C::from(1.0)));
+            row
+        }).collect()
+    }
+
+    /// The matrix of insphere, with the differences to the point and their squared lengths.
+    fn in_sphere_matrix<C: Coefficient>(&self, t: [usize; 4], v: usize) -> Vec<Vec<Polynomial<C>>> {
+        let e = self.coordinates(v);
+        t.iter().map(|&w| {
+            let mut row = self.coordinates(w).iter().zip(&e).map(|(x, y)| poly_sub(x, y)).collect::<Vec<_>>();
+            let lifted = row.iter().fold(Vec::new(), |sum, x| poly_add(&sum, &poly_mul(x, x)));
+            row.push(lifted);
+            row
+        }).collect()
+    }
+
+    fn orientation(&self, t: [usize; 4]) -> Option<Orientation> {
+        if self.is_finite(t) {
+            return self.tetrahedron(t).orientation();
+        }
+
+        let sign = filtered_sign(&poly_determinant(&self.orientation_matrix(t)),
+                                 || poly_determinant(&self.orientation_matrix(t)));
+
+        match sign {
+            Ordering::Less => Some(Orientation::Positive),
+            Ordering::Greater => Some(Orientation::Negative),
+            Ordering::Equal => None,
+        }
+    }
+
+    fn in_sphere(&self, t: [usize; 4], v: usize) -> Option<InCircleLocation> {
+        if self.is_finite(t) {
+            return self.tetrahedron(t).in_circle_test(&self.vertices[v]);
+        }
+
+        // Every tetrahedron has the orientation of the domain
+        let orientation = &self.orientation;
+        let sign = filtered_sign(&poly_determinant(&self.in_sphere_matrix(t, v)),
+                                 || poly_determinant(&self.in_sphere_matrix(t, v)));
+
+        // The point is inside if the signs of insphere and orient3d agree
+        Some(match sign {
+            Ordering::Equal => InCircleLocation::On,
+            sign if (sign == Ordering::Less) == (*orientation == Orientation::Positive) => InCircleLocation::Inside,
+            _ => InCircleLocation::Outside,
+        })
+    }
+
+    /// Finds a tetrahedron containing the vertex, walking from the last created tetrahedron
+    /// across faces which separate it from the vertex. In a Delaunay tetrahedralization the walk
+    /// always terminates.
+    fn locate(&self, v: usize) -> usize {
+        let mut current = self.last_created;
+
+        'walk: loop {
+            let t = self.tetrahedra[current];
+            for k in 0..4 {
+                let mut beyond = t;
+                beyond[k] = v;
+
+                if self.orientation(beyond).is_some_and(|o| o != self.orientation) {
+                    if let Some(n) = self.neighbors[current][k] {
+                        current = n;
+                        continue 'walk;
+                    }
+                }
+            }
+
+            return current;
+        }
+    }
+
+    /// The tetrahedra whose circumsphere contains the vertex. They are connected, so they are
+    /// found by a search over the neighbors of the tetrahedron containing it.
+    fn cavity(&self, start: usize, v: usize) -> Vec<usize> {
+        let mut cavity = vec!(start);
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut stack = vec!(start);
+
+        while let Some(t) = stack.pop() {
+            for n in self.neighbors[t].iter().filter_map(|&n| n) {
+                if visited.insert(n) && self.in_sphere(self.tetrahedra[n], v) == Some(InCircleLocation::Inside) {
+                    cavity.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+
+        cavity
+    }
+
+    /// Removes a tetrahedron that no other tetrahedron refers to, moving the last one into its
+    /// place.
+    fn remove_tetrahedron(&mut self, i: usize) {
+        let last = self.tetrahedra.len() - 1;
+        self.tetrahedra.swap_remove(i);
+        self.neighbors.swap_remove(i);
+
+        if i == last {
+            return;
+        }
+
+        let neighbors = self.neighbors[i];
+        for n in neighbors.iter().filter_map(|&n| n) {
+            for entry in self.neighbors[n].iter_mut().filter(|entry| **entry == Some(last)) {
+                *entry = Some(i);
+            }
+        }
+        if self.last_created == last {
+            self.last_created = i;
+        }
+    }
+}
+
+/// The arithmetic needed for the predicates of a domain at infinity, which are evaluated
+/// approximately first and exactly if that does not settle them.
+trait Coefficient: Clone + From<f64> {
+    fn plus(&self, other: &Self) -> Self;
+    fn times(&self, other: &Self) -> Self;
+    fn negated(&self) -> Self;
+}
+
+impl Coefficient for Expansion {
+    fn plus(&self, other: &Expansion) -> Expansion { self + other }
+    fn times(&self, other: &Expansion) -> Expansion { self * other }
+    fn negated(&self) -> Expansion { -self }
+}
+
+/// A value in floating-point arithmetic, and the same computation on the absolute values of the
+/// terms, which bounds its rounding error.
+#[derive(Clone)]
+struct Approximation {
+    value: f64,
+    magnitude: f64,
+}
+
+impl From<f64> for Approximation {
+    fn from(x: f64) -> Approximation {
+        Approximation { value: x, magnitude: x.abs() }
+    }
+}
+
+impl Coefficient for Approximation {
+    fn plus(&self, other: &Approximation) -> Approximation {
+        Approximation { value: self.value + other.value, magnitude: self.magnitude + other.magnitude }
+    }
+
+    fn times(&self, other: &Approximation) -> Approximation {
+        Approximation { value: self.value * other.value, magnitude: self.magnitude * other.magnitude }
+    }
+
+    fn negated(&self) -> Approximation {
+        Approximation { value: -self.value, magnitude: self.magnitude }
+    }
+}
+
+/// A polynomial in increasing degree.
+type Polynomial<C> = Vec<C>;
+
+fn poly_add<C: Coefficient>(a: &Polynomial<C>, b: &Polynomial<C>) -> Polynomial<C> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    long.iter().enumerate().map(|(i, x)| match short.get(i) {
+        Some(y) => x.plus(y),
+        None => x.clone(),
+    }).collect()
+}
+
+fn poly_sub<C: Coefficient>(a: &Polynomial<C>, b: &Polynomial<C>) -> Polynomial<C> {
+    poly_add(a, &b.iter().map(C::negated).collect())
+}
+
+fn poly_mul<C: Coefficient>(a: &Polynomial<C>, b: &Polynomial<C>) -> Polynomial<C> {
+    let mut product = vec!(C::from(0.0); (a.len() + b.len()).saturating_sub(1));
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] = product[i + j].plus(&x.times(y));
+        }
+    }
+    product
+}
+
+/// The determinant by cofactor expansion along the first row.
+fn poly_determinant<C: Coefficient>(m: &[Vec<Polynomial<C>>]) -> Polynomial<C> {
+    cofactor_expansion(m, &(0..m.len()).collect::<Vec<_>>())
+}
+
+/// The determinant of the last rows of the matrix, restricted to the given columns.
+fn cofactor_expansion<C: Coefficient>(m: &[Vec<Polynomial<C>>], columns: &[usize]) -> Polynomial<C> {
+    let row = &m[m.len() - columns.len()];
+    if columns.len() == 1 {
+        return row[columns[0]].clone();
+    }
+
+    (0..columns.len()).fold(Vec::new(), |det, i| {
+        let minor = columns.iter().enumerate().filter(|&(k, _)| k != i).map(|(_, &c)| c).collect::<Vec<_>>();
+        let term = poly_mul(&row[columns[i]], &cofactor_expansion(m, &minor));
+
+        if i % 2 == 0 { poly_add(&det, &term) } else { poly_sub(&det, &term) }
+    })
+}
+
+/// A generous bound on the relative error of the approximate coefficients, which take a few
+/// dozen operations at most.
+const POLYNOMIAL_ERRBOUND: f64 = 64.0 * f64::EPSILON;
+
+/// The sign of a polynomial as its variable goes to infinity, i.e. the sign of its leading
+/// non-zero coefficient. The exact coefficients are only computed if the approximate ones do
+/// not settle it.
+fn filtered_sign<F: FnOnce() -> Polynomial<Expansion>>(approximate: &[Approximation], exact: F) -> Ordering {
+    for a in approximate.iter().rev() {
+        let errbound = POLYNOMIAL_ERRBOUND * a.magnitude;
+        if a.value > errbound {
+            return Ordering::Greater;
+        }
+        if -a.value > errbound {
+            return Ordering::Less;
+        }
+
+        // Only a coefficient without any terms is certainly zero
+        if a.magnitude != 0.0 {
+            return exact().iter().rev().map(Expansion::sign).find(|&s| s != Ordering::Equal).unwrap_or(Ordering::Equal);
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// The face of a tetrahedron opposite to its `k`-th vertex.
+fn face(t: &[usize; 4], k: usize) -> [usize; 3] {
+    match k {
+        0 => [t[1], t[2], t[3]],
+        1 => [t[0], t[2], t[3]],
+        2 => [t[0], t[1], t[3]],
+        _ => [t[0], t[1], t[2]],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn domain() -> Tetrahedron<Point3D> {
+        Tetrahedron::new(Point3D::new(-100.0, -100.0, -100.0),
+                         Point3D::new(300.0, -100.0, -100.0),
+                         Point3D::new(-100.0, 300.0, -100.0),
+                         Point3D::new(-100.0, -100.0, 300.0))
+    }
+
+    #[test]
+    fn new_delaunay3d_degenerate_domain() {
+        let p = Point3D::new(0.0, 0.0, 0.0);
+        let t = Tetrahedron::new(p, Point3D::new(1.0, 0.0, 0.0), Point3D::new(0.0, 1.0, 0.0), Point3D::new(1.0, 1.0, 0.0));
+
+        assert!(Delaunay3D::new(t).is_none());
+    }
+
+    #[test]
+    fn insert_single_point() {
+        let mut d = Delaunay3D::new(domain()).unwrap();
+
+        assert_eq!(d.insert(Point3D::new(0.0, 0.0, 0.0)), Some(4));
+        assert_eq!(d.insert(Point3D::new(0.0, 0.0, 0.0)), None);
+        assert_eq!(d.insert(Point3D::new(500.0, 0.0, 0.0)), None);
+        assert_eq!(d.tetrahedra().len(), 4);
+        assert_eq!(d.faces().len(), 10);
+        assert!((0..4).all(|i| d.neighbors(i).iter().filter(|n| n.is_some()).count() == 3));
+    }
+
+    /// Checks the empty sphere property, the orientation of the tetrahedra and that neighbors
+    /// share their faces.
+    fn is_delaunay(d: &Delaunay3D) -> bool {
+        let tetrahedra = d.tetrahedra();
+
+        let empty_spheres = tetrahedra.iter().all(|t| {
+            t.orientation() == d.domain().orientation() &&
+                d.sites().iter().all(|p| t.in_circle_test(p) != Some(InCircleLocation::Inside))
+        });
+
+        let symmetric = (0..tetrahedra.len()).all(|i| {
+            d.neighbors(i).iter().filter_map(|&n| n).all(|n| d.neighbors(n).contains(&Some(i)))
+        });
+
+        let shared_faces = (0..tetrahedra.len()).all(|i| {
+            (0..4).all(|k| d.neighbors(i)[k].is_none_or(|n| {
+                face(&d.tetrahedron_indices()[i], k).iter().all(|v| d.tetrahedron_indices()[n].contains(v))
+            }))
+        });
+
+        // Only the four faces of the domain are on the boundary
+        let boundary = (0..tetrahedra.len()).map(|i| d.neighbors(i).iter().filter(|n| n.is_none()).count()).sum::<usize>();
+
+        empty_spheres && symmetric && shared_faces && boundary == 4 && d.faces().len() == (4 * tetrahedra.len() + 4) / 2
+    }
+
+    #[test]
+    fn insert_keeps_delaunay_property() {
+        fn insert_keeps_delaunay_property(points: Vec<(f64, f64, f64)>) -> TestResult {
+            let mut d = Delaunay3D::new(domain()).unwrap();
+
+            for &(x, y, z) in &points {
+                d.insert(Point3D::new(x % 50.0, y % 50.0, z % 50.0));
+            }
+
+            TestResult::from_bool(is_delaunay(&d))
+        }
+        quickcheck(insert_keeps_delaunay_property as fn(points: Vec<(f64, f64, f64)>) -> TestResult)
+    }
+
+    #[test]
+    fn insert_cospherical_lattice() {
+        let mut d = Delaunay3D::new(domain()).unwrap();
+        for i in 0..125 {
+            let p = Point3D::new((i % 5) as f64, (i / 5 % 5) as f64, (i / 25) as f64);
+            assert_eq!(d.insert(p), Some(i + 4));
+        }
+
+        assert!(is_delaunay(&d));
+
+        // The tetrahedra between the sites fill their convex hull
+        let volume = |t: &Tetrahedron<Point3D>| {
+            let (u, v, w) = ((t.p2.x - t.p1.x, t.p2.y - t.p1.y, t.p2.z - t.p1.z),
+                             (t.p3.x - t.p1.x, t.p3.y - t.p1.y, t.p3.z - t.p1.z),
+                             (t.p4.x - t.p1.x, t.p4.y - t.p1.y, t.p4.z - t.p1.z));
+            (u.0 * (v.1 * w.2 - v.2 * w.1) + u.1 * (v.2 * w.0 - v.0 * w.2) + u.2 * (v.0 * w.1 - v.1 * w.0)).abs() / 6.0
+        };
+        let cube = d.tetrahedra().iter().zip(d.tetrahedron_indices()).filter(|&(_, t)| t.iter().all(|&v| v >= 4)).map(|(t, _)| volume(t)).sum::<f64>();
+        assert!((cube - 64.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn insert_into_large_cavity() {
+        // Between two skew lines of sites there are quadratically many tetrahedra, so the cavity
+        // of a point between them has more tetrahedra than boundary faces
+        let mut d = Delaunay3D::new(domain()).unwrap();
+        for i in -10..11 {
+            d.insert(Point3D::new(f64::from(i), 0.0, -10.0));
+            d.insert(Point3D::new(0.5, f64::from(i) + 0.5, 10.0));
+        }
+        let before = d.tetrahedra().len();
+
+        assert!(d.insert(Point3D::new(0.1, 0.2, 0.05)).is_some());
+        assert!(d.tetrahedra().len() < before);
+        assert!(is_delaunay(&d));
+
+        for i in 0..20 {
+            d.insert(Point3D::new(f64::from(i) * 0.3 - 3.0, f64::from(i % 7) - 3.0, f64::from(i % 5) - 2.0));
+        }
+        assert!(is_delaunay(&d));
+    }
+
+    #[test]
+    fn unbounded_agrees_with_a_large_domain() {
+        fn unbounded_agrees_with_a_large_domain(points: Vec<(f64, f64, f64)>) -> TestResult {
+            let large = Tetrahedron::new(Point3D::new(-1e12, -1e12, -1e12),
+                                         Point3D::new(3e12, -1e12, -1e12),
+                                         Point3D::new(-1e12, 3e12, -1e12),
+                                         Point3D::new(-1e12, -1e12, 3e12));
+            let mut d = Delaunay3D::new(large).unwrap();
+            let mut unbounded = Delaunay3D::unbounded();
+
+            for &(x, y, z) in &points {
+                let p = Point3D::new(x % 50.0, y % 50.0, z % 50.0);
+                if d.insert(p) != unbounded.insert(p) {
+                    return TestResult::failed();
+                }
+            }
+
+            // The tetrahedra of the sites are the same, the large domain is far enough away
+            let sites = |d: &Delaunay3D| {
+                let mut tetrahedra = d.tetrahedron_indices().iter().filter(|t| t.iter().all(|&v| v >= 4)).map(|&t| {
+                    let mut t = t;
+                    t.sort();
+                    t
+                }).collect::<Vec<_>>();
+                tetrahedra.sort();
+                tetrahedra
+            };
+
+            TestResult::from_bool(sites(&d) == sites(&unbounded))
+        }
+        quickcheck(unbounded_agrees_with_a_large_domain as fn(points: Vec<(f64, f64, f64)>) -> TestResult)
+    }
+}
@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::fmt;
+
+/// The reason a predicate or a triangulation rejected its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryError {
+    /// A coordinate is NaN.
+    NotANumber,
+    /// A coordinate is infinite.
+    Infinite,
+    /// The coordinates are finite, but too large to evaluate the predicate without overflow.
+    Overflow,
+    /// The points of a triangle are collinear, or those of a tetrahedron coplanar.
+    Degenerate,
+    /// The point does not lie strictly inside the domain of the triangulation.
+    OutsideDomain,
+    /// The point is already a vertex of the triangulation.
+    Duplicate,
+    /// The vertex is not a site of the triangulation.
+    InvalidVertex,
+    /// The vertex is the end point of a constrained edge.
+    Constrained,
+    /// The constraint lies on the boundary of the domain, so it can't be split.
+    BoundaryConstraint,
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            GeometryError::NotANumber => "coordinate is NaN",
+            GeometryError::Infinite => "coordinate is infinite",
+            GeometryError::Overflow => "coordinates are too large",
+            GeometryError::Degenerate => "points are collinear or coplanar",
+            GeometryError::OutsideDomain => "point is not strictly inside the domain",
+            GeometryError::Duplicate => "point has already been inserted",
+            GeometryError::InvalidVertex => "vertex is not a site of the triangulation",
+            GeometryError::Constrained => "vertex is the end point of a constrained edge",
+            GeometryError::BoundaryConstraint => "constraint on the boundary of the domain can't be split",
+        };
+
+        f.write_str(description)
+    }
+}
+
+impl Error for GeometryError {}
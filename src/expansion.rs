@@ -0,0 +1,340 @@
+use predicates::{fast_two_sum, two_sum, two_diff, two_product, fast_expansion_sum_zeroelim, scale_expansion_zeroelim, estimate};
+
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Neg};
+
+/// An arbitrary precision number, represented exactly as the sum of floating-point components.
+///
+/// The components are non-overlapping and sorted by increasing magnitude, so the last one
+/// determines the sign and approximates the value. Zero components are eliminated, except for
+/// the single component of zero itself. Sums, differences and products are exact, as long as
+/// no component overflows or underflows.
+///
+/// This is the arithmetic the exact predicates are built upon, e.g. the orientation of three
+/// points is the sign of
+///
+/// ```
+/// # use voronoi::expansion::Expansion;
+/// # let (acx, acy, bcx, bcy) = (1.0, 2.0, 3.0, 4.0);
+/// let det = Expansion::product(acx, bcy) - Expansion::product(acy, bcx);
+/// ```
+///
+/// with the coordinate differences being exact expansions themselves in general.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    components: Vec<f64>,
+}
+
+impl Expansion {
+    pub fn new(x: f64) -> Expansion {
+        Expansion { components: vec!(x) }
+    }
+
+    /// The exact sum `a + b`.
+    pub fn sum(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_sum(a, b);
+        Expansion::from_pair(x, y)
+    }
+
+    /// The exact difference `a - b`.
+    pub fn difference(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_diff(a, b);
+        Expansion::from_pair(x, y)
+    }
+
+    /// The exact product `a * b`.
+    pub fn product(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_product(a, b);
+        Expansion::from_pair(x, y)
+    }
+
+    fn from_pair(x: f64, y: f64) -> Expansion {
+        if y == 0.0 {
+            Expansion::new(x)
+        } else {
+            Expansion { components: vec!(y, x) }
+        }
+    }
+
+    /// The components, from the smallest to the largest magnitude.
+    pub fn components(&self) -> &[f64] {
+        &self.components
+    }
+
+    /// Adds a single float to the expansion.
+    pub fn grow(&self, b: f64) -> Expansion {
+        let mut h = Vec::with_capacity(self.components.len() + 1);
+        let mut q = b;
+
+        for &enow in &self.components {
+            let (qnew, hh) = two_sum(q, enow);
+            q = qnew;
+            if hh != 0.0 {
+                h.push(hh);
+            }
+        }
+        if q != 0.0 || h.is_empty() {
+            h.push(q);
+        }
+
+        Expansion { components: h }
+    }
+
+    /// Multiplies the expansion with a single float.
+    pub fn scale(&self, b: f64) -> Expansion {
+        let mut h = vec!(0.0; 2 * self.components.len());
+        let len = scale_expansion_zeroelim(&self.components, b, &mut h);
+        h.truncate(len);
+
+        Expansion { components: h }
+    }
+
+    /// Returns an equal expansion with usually fewer components, where the largest component
+    /// approximates the value to within one unit in the last place.
+    pub fn compress(&self) -> Expansion {
+        let e = &self.components;
+        let mut h = e.clone();
+
+        let mut bottom = e.len() - 1;
+        let mut q = e[bottom];
+        for &enow in e[..bottom].iter().rev() {
+            let (qnew, small) = fast_two_sum(q, enow);
+            if small != 0.0 {
+                h[bottom] = qnew;
+                bottom -= 1;
+                q = small;
+            } else {
+                q = qnew;
+            }
+        }
+
+        let mut top = 0;
+        for hindex in (bottom + 1)..e.len() {
+            let (qnew, small) = fast_two_sum(h[hindex], q);
+            if small != 0.0 {
+                h[top] = small;
+                top += 1;
+            }
+            q = qnew;
+        }
+        h[top] = q;
+        h.truncate(top + 1);
+
+        Expansion { components: h }
+    }
+
+    /// An approximation of the value, the sum of the components in floating-point arithmetic.
+    pub fn estimate(&self) -> f64 {
+        estimate(&self.components)
+    }
+
+    /// The largest component of the compressed expansion, which approximates the value to within
+    /// one unit in the last place, with the correct sign.
+    pub fn approximate(&self) -> f64 {
+        let c = self.compress();
+        c.components[c.components.len() - 1]
+    }
+
+    /// Compares the exact value with zero.
+    pub fn sign(&self) -> Ordering {
+        let largest = self.components[self.components.len() - 1];
+        largest.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.sign() == Ordering::Equal
+    }
+}
+
+fn expansion_sum(e: &Expansion, f: &Expansion) -> Expansion {
+    let mut h = vec!(0.0; e.components.len() + f.components.len());
+    let len = fast_expansion_sum_zeroelim(&e.components, &f.components, &mut h);
+    h.truncate(len);
+
+    Expansion { components: h }
+}
+
+/// The exact determinant of a square matrix, by cofactor expansion along the first row.
+pub(crate) fn determinant(matrix: &[Vec<Expansion>]) -> Expansion {
+    if matrix.len() == 1 {
+        return matrix[0][0].clone();
+    }
+
+    let mut det = Expansion::default();
+    for (c, entry) in matrix[0].iter().enumerate() {
+        if entry.is_zero() {
+            continue;
+        }
+
+        let minor = matrix[1..].iter().map(|row| {
+            row.iter().enumerate().filter(|&(k, _)| k != c).map(|(_, e)| e.clone()).collect()
+        }).collect::<Vec<_>>();
+        let term = entry * &determinant(&minor);
+
+        det = if c % 2 == 0 { det + term } else { det - term };
+    }
+
+    det
+}
+
+impl Default for Expansion {
+    fn default() -> Expansion {
+        Expansion::new(0.0)
+    }
+}
+
+impl From<f64> for Expansion {
+    fn from(x: f64) -> Expansion {
+        Expansion::new(x)
+    }
+}
+
+impl Neg for &Expansion {
+    type Output = Expansion;
+
+    fn neg(self) -> Expansion {
+        Expansion { components: self.components.iter().map(|&c| -c).collect() }
+    }
+}
+
+impl Neg for Expansion {
+    type Output = Expansion;
+
+    fn neg(self) -> Expansion {
+        -&self
+    }
+}
+
+impl<'b> Add<&'b Expansion> for &Expansion {
+    type Output = Expansion;
+
+    fn add(self, other: &'b Expansion) -> Expansion {
+        expansion_sum(self, other)
+    }
+}
+
+impl<'b> Sub<&'b Expansion> for &Expansion {
+    type Output = Expansion;
+
+    fn sub(self, other: &'b Expansion) -> Expansion {
+        self + &-other
+    }
+}
+
+impl<'b> Mul<&'b Expansion> for &Expansion {
+    type Output = Expansion;
+
+    /// Sums up the expansion scaled by each component of the other one.
+    fn mul(self, other: &'b Expansion) -> Expansion {
+        let (long, short) = if self.components.len() >= other.components.len() { (self, other) } else { (other, self) };
+
+        short.components.iter().fold(Expansion::default(), |product, &c| expansion_sum(&product, &long.scale(c)))
+    }
+}
+
+impl Mul<f64> for &Expansion {
+    type Output = Expansion;
+
+    fn mul(self, b: f64) -> Expansion {
+        self.scale(b)
+    }
+}
+
+impl Mul<f64> for Expansion {
+    type Output = Expansion;
+
+    fn mul(self, b: f64) -> Expansion {
+        self.scale(b)
+    }
+}
+
+macro_rules! forward_binop {
+    ($imp:ident, $method:ident) => {
+        impl $imp for Expansion {
+            type Output = Expansion;
+
+            fn $method(self, other: Expansion) -> Expansion {
+                (&self).$method(&other)
+            }
+        }
+
+        impl<'a> $imp<&'a Expansion> for Expansion {
+            type Output = Expansion;
+
+            fn $method(self, other: &'a Expansion) -> Expansion {
+                (&self).$method(other)
+            }
+        }
+
+        impl<'a> $imp<Expansion> for &'a Expansion {
+            type Output = Expansion;
+
+            fn $method(self, other: Expansion) -> Expansion {
+                self.$method(&other)
+            }
+        }
+    }
+}
+
+forward_binop!(Add, add);
+forward_binop!(Sub, sub);
+forward_binop!(Mul, mul);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::quickcheck;
+
+    fn is_valid(e: &Expansion) -> bool {
+        e.components().windows(2).all(|w| w[0] != 0.0 && w[0].abs() < w[1].abs())
+    }
+
+    #[test]
+    fn sums_do_not_lose_small_components() {
+        let big = Expansion::new(1e100);
+        let e = &(&big + &Expansion::new(1.0)) - &big;
+
+        assert_eq!(e.components(), &[1.0]);
+        assert_eq!((&big - &big).components(), &[0.0]);
+        assert!((&big - &big).is_zero());
+        assert_eq!(big.grow(1.0).grow(-1e100).estimate(), 1.0);
+    }
+
+    #[test]
+    fn products_are_exact() {
+        let x = 1.0 + 2.0f64.powi(-52);
+        let square = Expansion::product(x, x);
+
+        // (1 + 2^-52)^2 = 1 + 2^-51 + 2^-104
+        assert_eq!(square.components(), &[2.0f64.powi(-104), 1.0 + 2.0f64.powi(-51)]);
+        assert_eq!((&square * &square).grow(-1.0).compress().sign(), Ordering::Greater);
+        assert_eq!(((&square - &Expansion::sum(1.0, 2.0f64.powi(-51))) * 2.0f64.powi(104)).components(), &[1.0]);
+    }
+
+    #[test]
+    fn compress_preserves_the_value() {
+        let e = Expansion::new(1e30).grow(1.0).grow(1e-30).grow(3.0);
+        let c = e.compress();
+
+        assert!(c.components().len() <= e.components().len());
+        assert!((&c - &e).is_zero());
+        assert!(is_valid(&c));
+    }
+
+    #[test]
+    fn expansion_arithmetic_matches_integer_arithmetic() {
+        fn expansion_arithmetic_matches_integer_arithmetic(a: i32, b: i32, c: i32, d: i32) -> bool {
+            // Shifting by 2^60 puts the terms far apart, the exact result still fits into 64 bits
+            let shift = 2.0f64.powi(60);
+            let lhs = Expansion::product(a as f64 * shift, b as f64) + Expansion::new(c as f64) - Expansion::product(d as f64, shift);
+            let rhs = ((a as i128 * b as i128) << 60) + c as i128 - ((d as i128) << 60);
+
+            let value = lhs.components().iter().fold(0i128, |s, &x| s + x as i128);
+            let sign = lhs.sign() == rhs.cmp(&0);
+
+            value == rhs && sign && is_valid(&lhs) && (&lhs * &lhs - &lhs * &lhs).is_zero()
+        }
+        quickcheck(expansion_arithmetic_matches_integer_arithmetic as fn(a: i32, b: i32, c: i32, d: i32) -> bool)
+    }
+}
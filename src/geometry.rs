@@ -1,33 +1,75 @@
-use libc::c_double;
+use error::GeometryError;
+use in_circle::*;
+use coordinate::Coordinate;
 
 pub trait Area<P> {
     fn area(&self) -> P;
 }
 
-#[repr(C)]
+/// Checking the coordinates before they are passed to the predicates.
+pub trait Validate {
+    /// Returns an error if a coordinate is NaN or infinite.
+    fn validate(&self) -> Result<(), GeometryError>;
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Point2D {
-    pub x: c_double,
-    pub y: c_double,
+pub struct Point2D<T = f64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2D<T> {
+    pub fn new(x: T, y: T) -> Point2D<T> {
+        Point2D { x, y }
+    }
 }
 
-impl Point2D {
-    pub fn new(x: c_double, y: c_double) -> Point2D {
-        Point2D { x: x, y: y }
+impl<T: Coordinate> Validate for Point2D<T> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.x.validate()?;
+        self.y.validate()
     }
 }
 
-#[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Point3D {
-    pub x: c_double,
-    pub y: c_double,
-    pub z: c_double,
+pub struct Point3D<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3D<T> {
+    pub fn new(x: T, y: T, z: T) -> Point3D<T> {
+        Point3D { x, y, z }
+    }
+}
+
+impl<T: Coordinate> Validate for Point3D<T> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.x.validate()?;
+        self.y.validate()?;
+        self.z.validate()
+    }
+}
+
+/// A point with a weight, as the sites of power diagrams and regular triangulations. The power
+/// distance of a point `q` to it is `|q - point|^2 - weight`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weighted<P> {
+    pub point: P,
+    pub weight: f64,
+}
+
+impl<P> Weighted<P> {
+    pub fn new(point: P, weight: f64) -> Weighted<P> {
+        Weighted { point, weight }
+    }
 }
 
-impl Point3D {
-    pub fn new(x: c_double, y: c_double, z: c_double) -> Point3D {
-        Point3D { x: x, y: y, z: z }
+impl<P: Validate> Validate for Weighted<P> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.point.validate()?;
+        self.weight.validate()
     }
 }
 
@@ -40,12 +82,19 @@ pub struct Edge<P> {
 impl<P> Edge<P> {
     pub fn new(p1: P, p2: P) -> Edge<P> {
         Edge {
-            p1: p1,
-            p2: p2,
+            p1,
+            p2,
         }
     }
 }
 
+impl<P: Validate> Validate for Edge<P> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.p1.validate()?;
+        self.p2.validate()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Triangle<P> {
     pub p1: P,
@@ -56,9 +105,9 @@ pub struct Triangle<P> {
 impl<P: Copy> Triangle<P> {
     pub fn new(p1: P, p2: P, p3: P) -> Triangle<P> {
         Triangle {
-            p1: p1,
-            p2: p2,
-            p3: p3,
+            p1,
+            p2,
+            p3,
         }
     }
 
@@ -67,10 +116,44 @@ impl<P: Copy> Triangle<P> {
     }
 }
 
+impl<P: Validate> Validate for Triangle<P> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.p1.validate()?;
+        self.p2.validate()?;
+        self.p3.validate()
+    }
+}
+
+impl Triangle<Point2D> {
+    /// The center of the circle through all three points. The result is not finite for
+    /// degenerate triangles.
+    pub fn circumcenter(&self) -> Point2D {
+        let (bx, by) = (self.p2.x - self.p1.x, self.p2.y - self.p1.y);
+        let (cx, cy) = (self.p3.x - self.p1.x, self.p3.y - self.p1.y);
+        let b2 = bx * bx + by * by;
+        let c2 = cx * cx + cy * cy;
+        let d = 2.0 * (bx * cy - by * cx);
+
+        Point2D::new(self.p1.x + (cy * b2 - by * c2) / d,
+                     self.p1.y + (bx * c2 - cx * b2) / d)
+    }
+
+    /// Like `circumcenter`, but returns an error for invalid or degenerate triangles, and if the
+    /// center cannot be represented.
+    pub fn try_circumcenter(&self) -> Result<Point2D, GeometryError> {
+        self.try_orientation()?;
+
+        let center = self.circumcenter();
+        center.validate().map_err(|_| GeometryError::Overflow)?;
+
+        Ok(center)
+    }
+}
+
 impl Area<f64> for Triangle<Point2D> {
     fn area(&self) -> f64 {
-        return 0.5 * ((self.p1.x - self.p3.x) * (self.p2.y - self.p1.y) -
-                      (self.p1.x - self.p2.x) * (self.p3.y - self.p1.y)).abs();
+        0.5 * ((self.p1.x - self.p3.x) * (self.p2.y - self.p1.y) -
+               (self.p1.x - self.p2.x) * (self.p3.y - self.p1.y)).abs()
     }
 }
 
@@ -85,14 +168,59 @@ pub struct Tetrahedron<P> {
 impl<P> Tetrahedron<P> {
     pub fn new(p1: P, p2: P, p3: P, p4: P) -> Tetrahedron<P> {
         Tetrahedron {
-            p1: p1,
-            p2: p2,
-            p3: p3,
-            p4: p4,
+            p1,
+            p2,
+            p3,
+            p4,
         }
     }
 }
 
+impl<P: Validate> Validate for Tetrahedron<P> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.p1.validate()?;
+        self.p2.validate()?;
+        self.p3.validate()?;
+        self.p4.validate()
+    }
+}
+
+impl Tetrahedron<Point3D> {
+    /// The center of the sphere through all four points. The result is not finite for
+    /// degenerate tetrahedra.
+    pub fn circumcenter(&self) -> Point3D {
+        let a = (self.p2.x - self.p1.x, self.p2.y - self.p1.y, self.p2.z - self.p1.z);
+        let b = (self.p3.x - self.p1.x, self.p3.y - self.p1.y, self.p3.z - self.p1.z);
+        let c = (self.p4.x - self.p1.x, self.p4.y - self.p1.y, self.p4.z - self.p1.z);
+
+        fn cross(u: (f64, f64, f64), v: (f64, f64, f64)) -> (f64, f64, f64) {
+            (u.1 * v.2 - u.2 * v.1, u.2 * v.0 - u.0 * v.2, u.0 * v.1 - u.1 * v.0)
+        }
+        fn dot(u: (f64, f64, f64), v: (f64, f64, f64)) -> f64 {
+            u.0 * v.0 + u.1 * v.1 + u.2 * v.2
+        }
+
+        let (bc, ca, ab) = (cross(b, c), cross(c, a), cross(a, b));
+        let (a2, b2, c2) = (dot(a, a), dot(b, b), dot(c, c));
+        let d = 2.0 * dot(a, bc);
+
+        Point3D::new(self.p1.x + (a2 * bc.0 + b2 * ca.0 + c2 * ab.0) / d,
+                     self.p1.y + (a2 * bc.1 + b2 * ca.1 + c2 * ab.1) / d,
+                     self.p1.z + (a2 * bc.2 + b2 * ca.2 + c2 * ab.2) / d)
+    }
+
+    /// Like `circumcenter`, but returns an error for invalid or degenerate tetrahedra, and if
+    /// the center cannot be represented.
+    pub fn try_circumcenter(&self) -> Result<Point3D, GeometryError> {
+        self.try_orientation()?;
+
+        let center = self.circumcenter();
+        center.validate().map_err(|_| GeometryError::Overflow)?;
+
+        Ok(center)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +253,36 @@ mod tests {
 
        assert_eq!(t.area(), 1.0)
    }
+
+   #[test]
+   fn triangle_circumcenter_test() {
+       let t = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0), Point2D::new(5.0, 5.0));
+
+       assert_eq!(t.circumcenter(), Point2D::new(5.0, 0.0));
+   }
+
+   #[test]
+   fn tetrahedron_circumcenter_test() {
+       let t = Tetrahedron::new(Point3D::new(0.0, 0.0, 0.0), Point3D::new(2.0, 0.0, 0.0),
+                                Point3D::new(0.0, 2.0, 0.0), Point3D::new(0.0, 0.0, 2.0));
+
+       assert_eq!(t.circumcenter(), Point3D::new(1.0, 1.0, 1.0));
+   }
+
+   #[test]
+   fn validate_and_try_circumcenter_test() {
+       use std::f64;
+
+       assert_eq!(Point2D::new(1.0, f64::NAN).validate(), Err(GeometryError::NotANumber));
+       assert_eq!(Point3D::new(1.0, 0.0, f64::NEG_INFINITY).validate(), Err(GeometryError::Infinite));
+
+       let t = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 0.0), Point2D::new(5.0, 5.0));
+       assert_eq!(t.try_circumcenter(), Ok(Point2D::new(5.0, 0.0)));
+       assert_eq!(Triangle::new(t.p1, t.p2, Point2D::new(20.0, 0.0)).try_circumcenter(), Err(GeometryError::Degenerate));
+       assert_eq!(Triangle::new(t.p1, t.p2, Point2D::new(f64::INFINITY, 0.0)).try_circumcenter(), Err(GeometryError::Infinite));
+
+       let t = Tetrahedron::new(Point3D::new(0.0, 0.0, 0.0), Point3D::new(2.0, 0.0, 0.0),
+                                Point3D::new(0.0, 2.0, 0.0), Point3D::new(2.0, 2.0, 0.0));
+       assert_eq!(t.try_circumcenter(), Err(GeometryError::Degenerate));
+   }
 }
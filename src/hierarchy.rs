@@ -0,0 +1,228 @@
+use geometry::*;
+use delaunay::*;
+use dcel::*;
+use random::XorShift;
+
+use std::collections::HashMap;
+
+/// Expected number of vertices of a level for each vertex of the level above.
+const RATIO: u64 = 30;
+const MAX_LEVELS: usize = 5;
+
+/// A Delaunay hierarchy for fast point location.
+///
+/// The lowest level is the full triangulation. Each point is also inserted into the level above
+/// with probability `1 / RATIO`, so the upper levels are random samples of decreasing size. A
+/// point is located by walking in the top level, then using the vertex closest to it as the
+/// start of the walk one level below. This takes expected logarithmic time, independent of the
+/// distribution of the queries.
+pub struct DelaunayHierarchy {
+    levels: Vec<Delaunay<Point2D>>,
+    /// For each level above the lowest, maps its vertices to the same vertex one level below.
+    down: Vec<HashMap<VertexHandle, VertexHandle>>,
+    /// The inverse of `down`.
+    up: Vec<HashMap<VertexHandle, VertexHandle>>,
+    random: XorShift,
+}
+
+impl DelaunayHierarchy {
+    pub fn new(t: Triangle<Point2D>) -> Option<DelaunayHierarchy> {
+        Some(DelaunayHierarchy {
+            levels: vec!(Delaunay::new(t)?),
+            down: Vec::new(),
+            up: Vec::new(),
+            random: XorShift::new(0x9e37_79b9_7f4a_7c15),
+        })
+    }
+
+    /// The full triangulation.
+    pub fn triangulation(&self) -> &Delaunay<Point2D> {
+        &self.levels[0]
+    }
+
+    /// The number of levels, including the full triangulation.
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Inserts a point into the full triangulation and a random number of upper levels.
+    ///
+    /// Returns the vertex of the new site in the full triangulation, or `None` if the point does
+    /// not lie strictly inside the domain or has already been inserted.
+    pub fn insert(&mut self, p: Point2D) -> Option<VertexHandle> {
+        let mut height = 0;
+        while height + 1 < MAX_LEVELS && self.random.next().is_multiple_of(RATIO) {
+            height += 1;
+        }
+
+        while self.levels.len() <= height {
+            let domain = self.levels[0].domain();
+            let upper = Delaunay::new(domain)?;
+
+            // The domain vertices are created in the same order on every level
+            let below = self.levels.last().unwrap().dcel().vertices();
+            let pairs = upper.dcel().vertices().into_iter().zip(below).collect::<Vec<_>>();
+            self.down.push(pairs.iter().cloned().collect());
+            self.up.push(pairs.iter().map(|&(a, b)| (b, a)).collect());
+            self.levels.push(upper);
+        }
+
+        let hints = self.hints(&p)?;
+        let mut vertices = Vec::with_capacity(height + 1);
+
+        for (level, &hint) in hints.iter().enumerate().take(height + 1) {
+            match self.levels[level].insert_with_hint(p, hint) {
+                Some(v) => { vertices.push(v); },
+                // Every point is in the lowest level, so it can only fail there
+                None => { return None; },
+            }
+        }
+
+        for level in 1..(height + 1) {
+            self.down[level - 1].insert(vertices[level], vertices[level - 1]);
+            self.up[level - 1].insert(vertices[level - 1], vertices[level]);
+        }
+
+        Some(vertices[0])
+    }
+
+    /// Removes a site, given by its vertex in the full triangulation, from all levels.
+    ///
+    /// Returns the point of the removed site, or `None` if `v` is not a site.
+    pub fn remove(&mut self, v: VertexHandle) -> Option<Point2D> {
+        let p = self.levels[0].remove(v)?;
+        let mut below = v;
+
+        for level in 1..self.levels.len() {
+            let above = match self.up[level - 1].remove(&below) {
+                Some(above) => above,
+                None => { break; },
+            };
+
+            self.down[level - 1].remove(&above);
+            self.levels[level].remove(above);
+            below = above;
+        }
+
+        Some(p)
+    }
+
+    /// Locates a point in the full triangulation.
+    pub fn locate(&self, p: &Point2D) -> Option<Location> {
+        let hints = self.hints(p)?;
+        self.levels[0].locate(p, hints[0])
+    }
+
+    /// A face to start the walk from on each level, found by walking down from the top.
+    fn hints(&self, p: &Point2D) -> Option<Vec<Option<FaceHandle>>> {
+        let mut hints = vec!(None; self.levels.len());
+        let mut hint = None;
+
+        for level in (0..self.levels.len()).rev() {
+            let d = &self.levels[level];
+            hints[level] = hint;
+
+            if level == 0 {
+                break;
+            }
+
+            hint = match nearest_vertex(d, p, d.locate(p, hint)?) {
+                Some(v) => self.levels[level - 1].incident_face(self.down[level - 1][&v]),
+                None => None,
+            };
+        }
+
+        Some(hints)
+    }
+}
+
+/// The vertex of the located simplex that is closest to `p`.
+fn nearest_vertex(d: &Delaunay<Point2D>, p: &Point2D, location: Location) -> Option<VertexHandle> {
+    let candidates = match location {
+        Location::OnVertex(v) => { return Some(v); },
+        Location::OnEdge(e) => vec!(d.dcel().origin(e), d.dcel().destination(e)),
+        Location::OnFace(f) => d.dcel().face_vertices(f),
+        Location::Outside => { return None; },
+    };
+
+    let distance = |v: &VertexHandle| {
+        let q = d.point(*v);
+        (q.x - p.x) * (q.x - p.x) + (q.y - p.y) * (q.y - p.y)
+    };
+
+    candidates.into_iter().min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use delaunay::Triangulation;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn domain() -> Triangle<Point2D> {
+        Triangle::new(Point2D::new(-1000.0, -1000.0), Point2D::new(1000.0, -1000.0), Point2D::new(0.0, 1000.0))
+    }
+
+    #[test]
+    fn hierarchy_builds_levels() {
+        let mut h = DelaunayHierarchy::new(domain()).unwrap();
+
+        for i in 0..2000 {
+            let (x, y) = ((i % 50) as f64 * 4.0 - 100.0, (i / 50) as f64 * 4.0 - 100.0);
+            assert!(h.insert(Point2D::new(x + 0.001 * (i % 7) as f64, y)).is_some());
+        }
+
+        assert!(h.levels() > 1);
+        assert_eq!(h.triangulation().vertices().len(), 2000);
+        assert_eq!(h.triangulation().triangles().len(), 2 * 2000 + 1);
+        assert_eq!(h.insert(Point2D::new(-100.0, -100.0)), None);
+    }
+
+    #[test]
+    fn hierarchy_remove() {
+        let mut h = DelaunayHierarchy::new(domain()).unwrap();
+        let vertices = (0..500).filter_map(|i| h.insert(Point2D::new((i * 37 % 101) as f64, (i * 59 % 103) as f64))).collect::<Vec<_>>();
+
+        for &v in &vertices[..400] {
+            assert!(h.remove(v).is_some());
+        }
+
+        assert_eq!(h.triangulation().vertices().len(), vertices.len() - 400);
+        assert!(vertices[400..].iter().all(|&v| {
+            h.locate(&h.triangulation().point(v)) == Some(Location::OnVertex(v))
+        }));
+        assert!((1..h.levels()).all(|l| h.levels[l].vertices().len() == h.down[l - 1].len() - 3));
+    }
+
+    #[test]
+    fn hierarchy_locate_matches_walk() {
+        fn hierarchy_locate_matches_walk(points: Vec<(f64, f64)>, queries: Vec<(f64, f64)>) -> TestResult {
+            let mut h = DelaunayHierarchy::new(domain()).unwrap();
+            for &(x, y) in &points {
+                h.insert(Point2D::new(x, y));
+            }
+
+            // Both sides of an edge are valid results
+            let d = h.triangulation();
+            let normalize = |l: Option<Location>| match l {
+                Some(Location::OnEdge(e)) => Some(Location::OnEdge(d.dcel().twin(e).map_or(e, |t| t.min(e)))),
+                l => l,
+            };
+
+            TestResult::from_bool(queries.iter().all(|&(x, y)| {
+                let p = Point2D::new(x, y);
+                normalize(h.locate(&p)) == normalize(d.locate(&p, None))
+            }))
+        }
+        quickcheck(hierarchy_locate_matches_walk as fn(points: Vec<(f64, f64)>, queries: Vec<(f64, f64)>) -> TestResult)
+    }
+
+    #[test]
+    fn triangulations_can_be_shared_between_threads() {
+        fn assert_sync<T: Sync>() {}
+
+        assert_sync::<Delaunay<Point2D>>();
+        assert_sync::<DelaunayHierarchy>();
+    }
+}
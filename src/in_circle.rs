@@ -1,25 +1,10 @@
 use geometry::*;
+use error::GeometryError;
 
-use libc::{c_double, c_void};
-use std::cmp::{Ordering};
-use std::sync::{Once, ONCE_INIT};
-
-static EXACTINIT: Once = ONCE_INIT;
-
-#[link(name = "predicates")]
-extern "C" {
-    fn exactinit() -> c_void;
-    fn orient2d(pa: *const Point2D, pb: *const Point2D, pc: *const Point2D) -> c_double;
-    fn incircle(pa: *const Point2D, pb: *const Point2D, pc: *const Point2D, pd: *const Point2D) -> c_double;
-    fn orient3d(pa: *const Point3D, pb: *const Point3D, pc: *const Point3D, pd: *const Point3D) -> c_double;
-    fn insphere(pa: *const Point3D, pb: *const Point3D, pc: *const Point3D, pd: *const Point3D, pe: *const Point3D) -> c_double;
-}
+use coordinate::Coordinate;
+use predicates::{orient2d, orient3d, power_test_2d, power_test_3d, orient_nd, insphere_nd};
 
-fn init_predicates() {
-    EXACTINIT.call_once(|| {
-        unsafe { exactinit() };
-    });
-}
+use std::cmp::{Ordering};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum InCircleLocation {
@@ -30,6 +15,23 @@ pub enum InCircleLocation {
 
 pub trait InCircleTestable<P> {
     fn in_circle_test(&self, point: &P) -> Option<InCircleLocation>;
+
+    /// Like `in_circle_test`, but tells why the test failed.
+    fn try_in_circle_test(&self, point: &P) -> Result<InCircleLocation, GeometryError>;
+
+    /// Tests many points at once, with the same results as `in_circle_test`.
+    fn in_circle_tests(&self, points: &[P]) -> Vec<Option<InCircleLocation>> {
+        points.iter().map(|p| self.in_circle_test(p)).collect()
+    }
+}
+
+/// Turns the determinants of a batch into locations, with all of them undefined for a degenerate
+/// simplex.
+fn dets_to_in_circle_locations(orientation: Option<Orientation>, dets: &[f64]) -> Vec<Option<InCircleLocation>> {
+    match orientation {
+        Some(o) => dets.iter().map(|&det| det_to_in_circle_location(o.to_f64_multiplier() * det)).collect(),
+        None => dets.iter().map(|_| None).collect(),
+    }
 }
 
 fn det_to_in_circle_location(det: f64) -> Option<InCircleLocation> {
@@ -40,34 +42,126 @@ fn det_to_in_circle_location(det: f64) -> Option<InCircleLocation> {
     })
 }
 
-impl InCircleTestable<Point2D> for Triangle<Point2D> {
-    fn in_circle_test(&self, point: &Point2D) -> Option<InCircleLocation> {
-        init_predicates();
-
+impl<T: Coordinate> InCircleTestable<Point2D<T>> for Triangle<Point2D<T>> {
+    fn in_circle_test(&self, point: &Point2D<T>) -> Option<InCircleLocation> {
         let orientation_multiplier: f64 = match self.orientation() {
             Some(p) => { p.to_f64_multiplier() },
             None => { return None; }
         };
 
-        let incircle_det = unsafe { incircle(&self.p1, &self.p2, &self.p3, point) };
+        let incircle_det = T::incircle(&self.p1, &self.p2, &self.p3, point);
 
         det_to_in_circle_location(orientation_multiplier * incircle_det)
     }
-}
 
-impl InCircleTestable<Point3D> for Tetrahedron<Point3D> {
-    fn in_circle_test(&self, point: &Point3D) -> Option<InCircleLocation> {
-        init_predicates();
+    fn try_in_circle_test(&self, point: &Point2D<T>) -> Result<InCircleLocation, GeometryError> {
+        let orientation = self.try_orientation()?;
+        point.validate()?;
+
+        let incircle_det = checked(T::incircle(&self.p1, &self.p2, &self.p3, point))?;
+
+        Ok(det_to_in_circle_location(orientation.to_f64_multiplier() * incircle_det).unwrap())
+    }
+
+    fn in_circle_tests(&self, points: &[Point2D<T>]) -> Vec<Option<InCircleLocation>> {
+        let mut dets = vec!(0.0; points.len());
+        T::incircle_batch(&self.p1, &self.p2, &self.p3, points, &mut dets);
 
+        dets_to_in_circle_locations(self.orientation(), &dets)
+    }
+}
+
+impl<T: Coordinate> InCircleTestable<Point3D<T>> for Tetrahedron<Point3D<T>> {
+    fn in_circle_test(&self, point: &Point3D<T>) -> Option<InCircleLocation> {
         let orientation_multiplier: f64 = match self.orientation() {
             Some(p) => { p.to_f64_multiplier() },
             None => { return None; }
         };
 
-        let incircle_det = unsafe { insphere(&self.p1, &self.p2, &self.p3, &self.p4, point) };
+        let incircle_det = T::insphere(&self.p1, &self.p2, &self.p3, &self.p4, point);
 
         det_to_in_circle_location(orientation_multiplier * incircle_det)
     }
+
+    fn try_in_circle_test(&self, point: &Point3D<T>) -> Result<InCircleLocation, GeometryError> {
+        let orientation = self.try_orientation()?;
+        point.validate()?;
+
+        let incircle_det = checked(T::insphere(&self.p1, &self.p2, &self.p3, &self.p4, point))?;
+
+        Ok(det_to_in_circle_location(orientation.to_f64_multiplier() * incircle_det).unwrap())
+    }
+
+    fn in_circle_tests(&self, points: &[Point3D<T>]) -> Vec<Option<InCircleLocation>> {
+        let mut dets = vec!(0.0; points.len());
+        T::insphere_batch(&self.p1, &self.p2, &self.p3, &self.p4, points, &mut dets);
+
+        dets_to_in_circle_locations(self.orientation(), &dets)
+    }
+}
+
+/// The weighted version of `InCircleTestable`, for regular triangulations and power diagrams. A
+/// point is `Inside` if its power distance to the circle or sphere orthogonal to the vertices is
+/// negative, i.e. it conflicts with the simplex.
+pub trait PowerTestable<P> {
+    fn power_test(&self, point: &P) -> Option<InCircleLocation>;
+
+    /// Like `power_test`, but tells why the test failed.
+    fn try_power_test(&self, point: &P) -> Result<InCircleLocation, GeometryError>;
+}
+
+impl Triangle<Weighted<Point2D>> {
+    /// The triangle of the unweighted points.
+    pub fn points(&self) -> Triangle<Point2D> {
+        Triangle::new(self.p1.point, self.p2.point, self.p3.point)
+    }
+}
+
+impl Tetrahedron<Weighted<Point3D>> {
+    /// The tetrahedron of the unweighted points.
+    pub fn points(&self) -> Tetrahedron<Point3D> {
+        Tetrahedron::new(self.p1.point, self.p2.point, self.p3.point, self.p4.point)
+    }
+}
+
+impl PowerTestable<Weighted<Point2D>> for Triangle<Weighted<Point2D>> {
+    fn power_test(&self, point: &Weighted<Point2D>) -> Option<InCircleLocation> {
+        let orientation = self.points().orientation()?;
+
+        let power_det = power_test_2d(&self.p1, &self.p2, &self.p3, point);
+
+        det_to_in_circle_location(orientation.to_f64_multiplier() * power_det)
+    }
+
+    fn try_power_test(&self, point: &Weighted<Point2D>) -> Result<InCircleLocation, GeometryError> {
+        self.validate()?;
+        let orientation = self.points().try_orientation()?;
+        point.validate()?;
+
+        let power_det = checked(power_test_2d(&self.p1, &self.p2, &self.p3, point))?;
+
+        Ok(det_to_in_circle_location(orientation.to_f64_multiplier() * power_det).unwrap())
+    }
+}
+
+impl PowerTestable<Weighted<Point3D>> for Tetrahedron<Weighted<Point3D>> {
+    fn power_test(&self, point: &Weighted<Point3D>) -> Option<InCircleLocation> {
+        let orientation = self.points().orientation()?;
+
+        let power_det = power_test_3d(&self.p1, &self.p2, &self.p3, &self.p4, point);
+
+        det_to_in_circle_location(orientation.to_f64_multiplier() * power_det)
+    }
+
+    fn try_power_test(&self, point: &Weighted<Point3D>) -> Result<InCircleLocation, GeometryError> {
+        self.validate()?;
+        let orientation = self.points().try_orientation()?;
+        point.validate()?;
+
+        let power_det = checked(power_test_3d(&self.p1, &self.p2, &self.p3, &self.p4, point))?;
+
+        Ok(det_to_in_circle_location(orientation.to_f64_multiplier() * power_det).unwrap())
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -78,6 +172,9 @@ pub enum Orientation {
 
 pub trait Orientable {
     fn orientation(&self) -> Option<Orientation>;
+
+    /// Like `orientation`, but tells why there is none.
+    fn try_orientation(&self) -> Result<Orientation, GeometryError>;
 }
 
 impl Orientation {
@@ -89,24 +186,41 @@ impl Orientation {
     }
 }
 
-impl Orientable for Tetrahedron<Point3D> {
+impl<T: Coordinate> Orientable for Tetrahedron<Point3D<T>> {
     fn orientation(&self) -> Option<Orientation> {
-        init_predicates();
-
-        let orientation_det = unsafe { orient3d(&self.p1, &self.p2, &self.p3, &self.p4) };
+        let orientation_det = T::orient3d(&self.p1, &self.p2, &self.p3, &self.p4);
 
         det_to_orientation(orientation_det)
     }
+
+    fn try_orientation(&self) -> Result<Orientation, GeometryError> {
+        self.validate()?;
+
+        let orientation_det = checked(T::orient3d(&self.p1, &self.p2, &self.p3, &self.p4))?;
+
+        det_to_orientation(orientation_det).ok_or(GeometryError::Degenerate)
+    }
 }
 
-impl Orientable for Triangle<Point2D> {
+impl<T: Coordinate> Orientable for Triangle<Point2D<T>> {
     fn orientation(&self) -> Option<Orientation> {
-        init_predicates();
-
-        let orientation_det = unsafe { orient2d(&self.p1, &self.p2, &self.p3) };
+        let orientation_det = T::orient2d(&self.p1, &self.p2, &self.p3);
 
         det_to_orientation(orientation_det)
     }
+
+    fn try_orientation(&self) -> Result<Orientation, GeometryError> {
+        self.validate()?;
+
+        let orientation_det = checked(T::orient2d(&self.p1, &self.p2, &self.p3))?;
+
+        det_to_orientation(orientation_det).ok_or(GeometryError::Degenerate)
+    }
+}
+
+/// Rejects determinants that overflowed, for finite inputs.
+fn checked(det: f64) -> Result<f64, GeometryError> {
+    if det.is_finite() { Ok(det) } else { Err(GeometryError::Overflow) }
 }
 
 fn det_to_orientation(det: f64) -> Option<Orientation> {
@@ -117,89 +231,246 @@ fn det_to_orientation(det: f64) -> Option<Orientation> {
     }
 }
 
+impl<T: Coordinate> Edge<Point2D<T>> {
+    /// The orientations of the triangles formed by the edge and each of the points, the same as
+    /// `Triangle::new(self.p1, self.p2, point).orientation()`.
+    pub fn orientations(&self, points: &[Point2D<T>]) -> Vec<Option<Orientation>> {
+        let mut dets = vec!(0.0; points.len());
+        T::orient2d_batch(&self.p1, &self.p2, points, &mut dets);
+
+        dets.into_iter().map(det_to_orientation).collect()
+    }
+}
+
+impl<T: Coordinate> Triangle<Point3D<T>> {
+    /// The orientations of the tetrahedra formed by the triangle and each of the points, the same
+    /// as `Tetrahedron::new(self.p1, self.p2, self.p3, point).orientation()`.
+    pub fn orientations(&self, points: &[Point3D<T>]) -> Vec<Option<Orientation>> {
+        let mut dets = vec!(0.0; points.len());
+        T::orient3d_batch(&self.p1, &self.p2, &self.p3, points, &mut dets);
+
+        dets.into_iter().map(det_to_orientation).collect()
+    }
+}
+
+fn validate_coordinates<const D: usize>(points: &[[f64; D]]) -> Result<(), GeometryError> {
+    points.iter().flat_map(|p| p.iter()).try_for_each(|&x| x.validate())
+}
+
+/// The orientation of the simplex of `D + 1` points in `D` dimensions, the same as
+/// `Triangle::orientation` and `Tetrahedron::orientation` in two and three dimensions. Returns
+/// `None` if the points lie in a hyperplane.
+///
+/// Panics if the number of points is not `D + 1`.
+pub fn orient<const D: usize>(points: &[[f64; D]]) -> Option<Orientation> {
+    det_to_orientation(orient_nd(points))
+}
+
+/// Like `orient`, but tells why there is no orientation.
+pub fn try_orient<const D: usize>(points: &[[f64; D]]) -> Result<Orientation, GeometryError> {
+    validate_coordinates(points)?;
+
+    let orientation_det = checked(orient_nd(points))?;
+
+    det_to_orientation(orientation_det).ok_or(GeometryError::Degenerate)
+}
+
+/// Tests the point against the sphere through the `D + 1` points of a simplex in `D`
+/// dimensions, the same as `in_circle_test` in two and three dimensions.
+///
+/// Panics if the number of points of the simplex is not `D + 1`.
+pub fn insphere<const D: usize>(simplex: &[[f64; D]], point: &[f64; D]) -> Option<InCircleLocation> {
+    let orientation_multiplier = orient(simplex)?.to_f64_multiplier();
+
+    let mut points = simplex.to_vec();
+    points.push(*point);
+
+    det_to_in_circle_location(orientation_multiplier * insphere_nd(&points))
+}
+
+/// Like `insphere`, but tells why the test failed.
+pub fn try_insphere<const D: usize>(simplex: &[[f64; D]], point: &[f64; D]) -> Result<InCircleLocation, GeometryError> {
+    let orientation = try_orient(simplex)?;
+    validate_coordinates(&[*point])?;
+
+    let mut points = simplex.to_vec();
+    points.push(*point);
+    let insphere_det = checked(insphere_nd(&points))?;
+
+    Ok(det_to_in_circle_location(orientation.to_f64_multiplier() * insphere_det).unwrap())
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TrianglePointLocation<P> {
     Inside,
     Outside,
-    OnEdge(Edge<P>)
+    /// On the edge, strictly between its end points.
+    OnEdge(Edge<P>),
+    OnVertex(P),
 }
 
 #[derive(Debug, PartialEq)]
 pub enum TetrahedronPointLocation<P> {
     Inside,
     Outside,
+    /// On the face, strictly inside of it.
     OnFace(Triangle<P>),
-    OnEdge(Edge<P>)
+    /// On the edge, strictly between its end points.
+    OnEdge(Edge<P>),
+    OnVertex(P),
+}
+
+/// The barycentric coordinates of a point with respect to a triangle or tetrahedron, i.e. the
+/// weights of the vertices that interpolate the point.
+///
+/// The coordinate of a vertex is the ratio of the orientation determinant with the vertex
+/// replaced by the point to the one of the simplex. The determinants are evaluated with the
+/// exact predicates, so the signs are exact: a coordinate is zero exactly if the point lies on
+/// the opposite facet. The values are approximations, which sum up to one within rounding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Barycentric<const N: usize> {
+    /// The exact signs of the coordinates, positive towards the inside.
+    pub signs: [Ordering; N],
+    pub coordinates: [f64; N],
+}
+
+impl<const N: usize> Barycentric<N> {
+    /// Computes the coordinates from the determinant of the simplex and those with each vertex
+    /// replaced by the point.
+    fn from_determinants(det: f64, dets: [f64; N]) -> Option<Barycentric<N>> {
+        if det == 0.0 || !det.is_finite() || dets.iter().any(|d| d.is_nan()) {
+            return None;
+        }
+
+        let mut signs = [Ordering::Equal; N];
+        for (sign, d) in signs.iter_mut().zip(&dets) {
+            *sign = d.partial_cmp(&0.0)?;
+            if det < 0.0 {
+                *sign = sign.reverse();
+            }
+        }
+
+        let mut coordinates = [0.0; N];
+        for (c, d) in coordinates.iter_mut().zip(&dets) {
+            *c = d / det;
+        }
+
+        Some(Barycentric { signs, coordinates })
+    }
+
+    /// Interpolates values given at the vertices.
+    pub fn interpolate(&self, values: &[f64; N]) -> f64 {
+        self.coordinates.iter().zip(values).map(|(c, v)| c * v).sum()
+    }
 }
 
 impl Triangle<Point2D> {
-    pub fn locate(&self, p: &Point2D) -> Option<TrianglePointLocation<Point2D>> {
-        let orientation = match self.orientation() {
-            Some(o) => o,
-            None => { return None; },
-        };
+    /// The barycentric coordinates of `p`, or `None` if the triangle is degenerate.
+    pub fn barycentric(&self, p: &Point2D) -> Option<Barycentric<3>> {
+        Barycentric::from_determinants(orient2d(&self.p1, &self.p2, &self.p3),
+                                       [orient2d(p, &self.p2, &self.p3),
+                                        orient2d(&self.p1, p, &self.p3),
+                                        orient2d(&self.p1, &self.p2, p)])
+    }
 
-        // Fix orientation if needed
-        let t: Triangle<Point2D>  = if orientation == Orientation::Positive { *self } else { Triangle::new(self.p1, self.p3, self.p2) };
+    /// Classifies a point by the signs of its barycentric coordinates.
+    ///
+    /// The point is outside if any coordinate is negative. Otherwise it lies on the edges
+    /// opposite to the vertices with a zero coordinate, i.e. on a vertex if there are two of
+    /// them. Edges are reported in the order of the triangle with positive orientation.
+    pub fn locate(&self, p: &Point2D) -> Option<TrianglePointLocation<Point2D>> {
+        let signs = self.barycentric(p)?.signs;
 
-        let orientations = t.edges().into_iter().map( |&edge| {
-           let t = Triangle::new(edge.p1, edge.p2, *p);
-           ((edge.p1, edge.p2), t.orientation())
-        }).collect::<Vec<((_,_), _)>>();
+        if signs.contains(&Ordering::Less) {
+            return Some(TrianglePointLocation::Outside);
+        }
 
-        if orientations.iter().all( |&((_,_), ref o)| *o == Some(Orientation::Positive)) {
-           return Some(TrianglePointLocation::Inside);
+        // Fix orientation if needed, keeping track of the vertex of each coordinate
+        let (t, index) = if self.orientation()? == Orientation::Positive {
+            ([self.p1, self.p2, self.p3], [0, 1, 2])
+        } else {
+            ([self.p1, self.p3, self.p2], [0, 2, 1])
         };
 
-        if !orientations.iter().any( |&((_,_), ref o)| *o == None) {
-           return Some(TrianglePointLocation::Outside);
-        };
+        let zero = (0..3).filter(|&k| signs[index[k]] == Ordering::Equal).collect::<Vec<_>>();
+        let nonzero = (0..3).filter(|k| !zero.contains(k)).map(|k| t[k]).collect::<Vec<_>>();
 
-        let (p1, p2) = orientations.iter().filter_map(|&((a, b), ref o)| if *o == None {Some((a, b))} else {None} ).next().unwrap();
+        Some(match zero.len() {
+            0 => TrianglePointLocation::Inside,
+            1 => TrianglePointLocation::OnEdge(Edge::new(t[(zero[0] + 1) % 3], t[(zero[0] + 2) % 3])),
+            _ => TrianglePointLocation::OnVertex(nonzero[0]),
+        })
+    }
+
+    /// Like `locate`, but tells why the point could not be located.
+    pub fn try_locate(&self, p: &Point2D) -> Result<TrianglePointLocation<Point2D>, GeometryError> {
+        self.try_orientation()?;
+        p.validate()?;
 
-        return Some(TrianglePointLocation::OnEdge(Edge::new(p1, p2)));
+        self.locate(p).ok_or(GeometryError::Degenerate)
     }
 }
 
 impl Tetrahedron<Point3D> {
-    pub fn locate(&self, p: &Point3D) -> Option<TetrahedronPointLocation<Point3D>> {
-        let orientation = match self.orientation() {
-            Some(o) => o,
-            None => { return None; },
-        };
-
-        // Fix orientation if needed
-        let t: Tetrahedron<Point3D>  = if orientation == Orientation::Positive { *self } else { Tetrahedron::new(self.p1, self.p2, self.p4, self.p3) };
+    /// The barycentric coordinates of `p`, or `None` if the tetrahedron is degenerate.
+    pub fn barycentric(&self, p: &Point3D) -> Option<Barycentric<4>> {
+        Barycentric::from_determinants(orient3d(&self.p1, &self.p2, &self.p3, &self.p4),
+                                       [orient3d(p, &self.p2, &self.p3, &self.p4),
+                                        orient3d(&self.p1, p, &self.p3, &self.p4),
+                                        orient3d(&self.p1, &self.p2, p, &self.p4),
+                                        orient3d(&self.p1, &self.p2, &self.p3, p)])
+    }
 
-        let triangles = [(t.p1, t.p2, t.p3),
-                         (t.p2, t.p4, t.p3),
-                         (t.p3, t.p4, t.p1),
-                         (t.p4, t.p2, t.p1),
-                        ];
+    /// Classifies a point by the signs of its barycentric coordinates, like `Triangle::locate`.
+    /// A point with two zero coordinates lies on the edge between the other two vertices, and a
+    /// point with three on the remaining vertex.
+    pub fn locate(&self, p: &Point3D) -> Option<TetrahedronPointLocation<Point3D>> {
+        let signs = self.barycentric(p)?.signs;
 
-        let orientations = triangles.iter().map( |&(p1, p2, p3)| {
-            let t = Tetrahedron::new(p1, p2, p3, *p);
-            ((p1, p2, p3), t.orientation())
-        }).collect::<Vec<((_,_,_), _)>>();
+        if signs.contains(&Ordering::Less) {
+            return Some(TetrahedronPointLocation::Outside);
+        }
 
-        if orientations.iter().all( |&((_,_,_), ref o)| *o == Some(Orientation::Positive)) {
-            return Some(TetrahedronPointLocation::Inside);
+        // Fix orientation if needed, keeping track of the vertex of each coordinate
+        let (t, index) = if self.orientation()? == Orientation::Positive {
+            ([self.p1, self.p2, self.p3, self.p4], [0, 1, 2, 3])
+        } else {
+            ([self.p1, self.p2, self.p4, self.p3], [0, 1, 3, 2])
         };
 
-        if !orientations.iter().any( |&((_,_,_), ref o)| *o == None) {
-            return Some(TetrahedronPointLocation::Outside);
-        };
+        // The faces opposite to each vertex
+        let faces = [(t[1], t[3], t[2]),
+                     (t[2], t[3], t[0]),
+                     (t[3], t[1], t[0]),
+                     (t[0], t[1], t[2]),
+                    ];
+
+        let zero = (0..4).filter(|&k| signs[index[k]] == Ordering::Equal).collect::<Vec<_>>();
+        let nonzero = (0..4).filter(|k| !zero.contains(k)).map(|k| t[k]).collect::<Vec<_>>();
+
+        Some(match zero.len() {
+            0 => TetrahedronPointLocation::Inside,
+            1 => {
+                let (p1, p2, p3) = faces[zero[0]];
+                TetrahedronPointLocation::OnFace(Triangle::new(p1, p2, p3))
+            },
+            2 => TetrahedronPointLocation::OnEdge(Edge::new(nonzero[0], nonzero[1])),
+            _ => TetrahedronPointLocation::OnVertex(nonzero[0]),
+        })
+    }
 
-        let (p1, p2, p3) = orientations.iter().filter_map(|&((a, b, c), ref o)| if *o == None {Some((a, b, c))} else {None} ).next().unwrap();
+    /// Like `locate`, but tells why the point could not be located.
+    pub fn try_locate(&self, p: &Point3D) -> Result<TetrahedronPointLocation<Point3D>, GeometryError> {
+        self.try_orientation()?;
+        p.validate()?;
 
-        return Some(TetrahedronPointLocation::OnFace(Triangle::new(p1, p2, p3)));
+        self.locate(p).ok_or(GeometryError::Degenerate)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use geometry::*;
 
     use std::f64;
     use std::f64::consts;
@@ -249,6 +520,102 @@ mod tests {
         assert_eq!(t_neg.locate(&d_on), Some(TetrahedronPointLocation::OnFace(Triangle::new(p1, p2, p3))));
     }
 
+    #[test]
+    fn triangle_point_location_on_vertices_and_edges() {
+        let (p1, p2, p3) = (Point2D::new(0.0, 0.0), Point2D::new(0.0, 10.0), Point2D::new(10.0, 0.0));
+
+        for t in &[Triangle::new(p1, p2, p3), Triangle::new(p2, p1, p3)] {
+            for &p in &[p1, p2, p3] {
+                assert_eq!(t.locate(&p), Some(TrianglePointLocation::OnVertex(p)));
+            }
+
+            let on_edge = |p: &Point2D, a: Point2D, b: Point2D| match t.locate(p) {
+                Some(TrianglePointLocation::OnEdge(e)) => (e.p1 == a && e.p2 == b) || (e.p1 == b && e.p2 == a),
+                _ => false,
+            };
+            assert!(on_edge(&Point2D::new(0.0, 5.0), p1, p2));
+            assert!(on_edge(&Point2D::new(5.0, 0.0), p1, p3));
+            assert!(on_edge(&Point2D::new(5.0, 5.0), p2, p3));
+
+            // On the line through an edge, but beyond its end points
+            assert_eq!(t.locate(&Point2D::new(0.0, 20.0)), Some(TrianglePointLocation::Outside));
+            assert_eq!(t.locate(&Point2D::new(-5.0, 0.0)), Some(TrianglePointLocation::Outside));
+        }
+    }
+
+    #[test]
+    fn tetrahedron_point_location_on_vertices_and_edges() {
+        let p1 = Point3D::new(0.0, 0.0, 0.0);
+        let p2 = Point3D::new(10.0, 0.0, 0.0);
+        let p3 = Point3D::new(0.0, 10.0, 0.0);
+        let p4 = Point3D::new(0.0, 0.0, 10.0);
+
+        for t in &[Tetrahedron::new(p1, p2, p3, p4), Tetrahedron::new(p2, p1, p3, p4)] {
+            for &p in &[p1, p2, p3, p4] {
+                assert_eq!(t.locate(&p), Some(TetrahedronPointLocation::OnVertex(p)));
+            }
+
+            let on_edge = |p: &Point3D, a: Point3D, b: Point3D| match t.locate(p) {
+                Some(TetrahedronPointLocation::OnEdge(e)) => (e.p1 == a && e.p2 == b) || (e.p1 == b && e.p2 == a),
+                _ => false,
+            };
+            assert!(on_edge(&Point3D::new(5.0, 0.0, 0.0), p1, p2));
+            assert!(on_edge(&Point3D::new(5.0, 5.0, 0.0), p2, p3));
+            assert!(on_edge(&Point3D::new(0.0, 0.0, 5.0), p1, p4));
+
+            assert_eq!(t.locate(&Point3D::new(20.0, 0.0, 0.0)), Some(TetrahedronPointLocation::Outside));
+            assert_eq!(t.locate(&Point3D::new(5.0, 10.0, 0.0)), Some(TetrahedronPointLocation::Outside));
+        }
+    }
+
+    #[test]
+    fn barycentric_coordinates() {
+        let t = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(0.0, 10.0), Point2D::new(10.0, 0.0));
+
+        let b = t.barycentric(&Point2D::new(2.0, 3.0)).unwrap();
+        assert_eq!(b.signs, [Ordering::Greater; 3]);
+        assert_eq!(b.coordinates, [0.5, 0.3, 0.2]);
+        assert!((b.interpolate(&[1.0, 2.0, 3.0]) - 1.7).abs() < 1e-12);
+
+        let b = t.barycentric(&Point2D::new(0.0, 5.0)).unwrap();
+        assert_eq!(b.signs, [Ordering::Greater, Ordering::Greater, Ordering::Equal]);
+        assert_eq!(t.barycentric(&Point2D::new(-1.0, 5.0)).unwrap().signs[2], Ordering::Less);
+        assert_eq!(Triangle::new(t.p1, t.p1, t.p2).barycentric(&t.p3), None);
+
+        let t = Tetrahedron::new(Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 0.0, 0.0),
+                                 Point3D::new(0.0, 10.0, 0.0), Point3D::new(0.0, 0.0, 10.0));
+        let b = t.barycentric(&Point3D::new(1.0, 2.0, 3.0)).unwrap();
+        assert_eq!(b.signs, [Ordering::Greater; 4]);
+        assert_eq!(b.coordinates, [0.4, 0.1, 0.2, 0.3]);
+        assert_eq!(t.barycentric(&Point3D::new(5.0, 5.0, 0.0)).unwrap().signs,
+                   [Ordering::Equal, Ordering::Greater, Ordering::Greater, Ordering::Equal]);
+    }
+
+    #[test]
+    fn barycentric_coordinates_reproduce_the_point() {
+        fn barycentric_coordinates_reproduce_the_point(points: Vec<(f64, f64)>) -> TestResult {
+            if points.len() < 4 {
+                return TestResult::discard();
+            }
+            let p = points.iter().map(|&(x, y)| Point2D::new(x % 100.0, y % 100.0)).collect::<Vec<_>>();
+            let t = Triangle::new(p[0], p[1], p[2]);
+
+            let b = match t.barycentric(&p[3]) {
+                Some(b) => b,
+                None => { return TestResult::discard(); },
+            };
+            let x = b.interpolate(&[p[0].x, p[1].x, p[2].x]);
+            let y = b.interpolate(&[p[0].y, p[1].y, p[2].y]);
+
+            // The rounding errors grow with the magnitude of the coordinates
+            let tolerance = 1e-10 * b.coordinates.iter().map(|c| c.abs()).sum::<f64>() * 100.0;
+            let signs_match = b.signs.iter().zip(&b.coordinates).all(|(&s, &c)| c.partial_cmp(&0.0) == Some(s));
+
+            TestResult::from_bool(signs_match && (x - p[3].x).abs() <= tolerance && (y - p[3].y).abs() <= tolerance)
+        }
+        quickcheck(barycentric_coordinates_reproduce_the_point as fn(points: Vec<(f64, f64)>) -> TestResult)
+    }
+
     #[test]
     fn in_circle_2d() {
         let p1 = Point2D::new(0.0, 0.0);
@@ -295,6 +662,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::type_complexity)]
     fn in_circle_tetrahedron_constructed_from_sphere() {
         // Specify the range of number we want to check, as we have to map them to a different
         // interval below.
@@ -432,6 +800,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::partialeq_to_none)]
     fn in_circle_2d_doubled_point_in_triangle() {
         fn in_circle_2d_doubled_point_in_triangle(p1: (f64, f64), p2: (f64, f64), p_test: (f64, f64)) -> TestResult {
             let point1 = Point2D::new(p1.0, p1.1);
@@ -469,4 +838,169 @@ mod tests {
         assert_eq!(t.in_circle_test(&d_on), Some(InCircleLocation::On));
     }
 
+    #[test]
+    fn try_predicates_report_errors() {
+        let t = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(0.0, 1.0), Point2D::new(1.0, 1.0));
+        let collinear = Triangle::new(Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0));
+        let huge = Triangle::new(Point2D::new(-1e300, 0.0), Point2D::new(1e300, 1e300), Point2D::new(0.0, -1e300));
+
+        assert_eq!(t.try_orientation(), Ok(Orientation::Positive));
+        assert_eq!(collinear.try_orientation(), Err(GeometryError::Degenerate));
+        assert_eq!(huge.try_orientation(), Err(GeometryError::Overflow));
+        assert_eq!(Triangle::new(t.p1, t.p2, Point2D::new(f64::NAN, 0.0)).try_orientation(), Err(GeometryError::NotANumber));
+
+        assert_eq!(t.try_in_circle_test(&Point2D::new(0.5, 0.5)), Ok(InCircleLocation::Inside));
+        assert_eq!(t.try_in_circle_test(&Point2D::new(f64::INFINITY, 0.5)), Err(GeometryError::Infinite));
+        assert_eq!(collinear.try_in_circle_test(&Point2D::new(0.5, 0.5)), Err(GeometryError::Degenerate));
+
+        assert_eq!(t.try_locate(&Point2D::new(0.5, 0.5)), Ok(TrianglePointLocation::OnEdge(Edge::new(Point2D::new(1.0, 1.0), Point2D::new(0.0, 0.0)))));
+        assert_eq!(collinear.try_locate(&Point2D::new(0.5, 0.5)), Err(GeometryError::Degenerate));
+
+        let tetrahedron = Tetrahedron::new(Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 0.0, 0.0), Point3D::new(0.0, 1.0, 0.0), Point3D::new(0.0, 0.0, 1.0));
+        let flat = Tetrahedron::new(tetrahedron.p1, tetrahedron.p2, tetrahedron.p3, Point3D::new(1.0, 1.0, 0.0));
+
+        assert!(tetrahedron.try_orientation().is_ok());
+        assert_eq!(flat.try_orientation(), Err(GeometryError::Degenerate));
+        assert_eq!(tetrahedron.try_in_circle_test(&Point3D::new(0.1, 0.1, 0.1)), Ok(InCircleLocation::Inside));
+        assert_eq!(tetrahedron.try_locate(&Point3D::new(f64::NAN, 0.1, 0.1)), Err(GeometryError::NotANumber));
+        assert_eq!(flat.try_locate(&Point3D::new(0.1, 0.1, 0.1)), Err(GeometryError::Degenerate));
+    }
+
+    #[test]
+    fn batches_match_single_tests() {
+        fn batches_match_single_tests(coordinates: Vec<(i8, i8, i8)>) -> TestResult {
+            // A coarse grid, so that degenerate configurations are common
+            let p2 = coordinates.iter().map(|&(x, y, _)| Point2D::new((x % 4) as f64, (y % 4) as f64)).collect::<Vec<_>>();
+            let p3 = coordinates.iter().map(|&(x, y, z)| Point3D::new((x % 4) as f64, (y % 4) as f64, (z % 4) as f64)).collect::<Vec<_>>();
+            if p2.len() < 4 {
+                return TestResult::discard();
+            }
+
+            let edge = Edge::new(p2[0], p2[1]);
+            let triangle = Triangle::new(p2[0], p2[1], p2[2]);
+            let face = Triangle::new(p3[0], p3[1], p3[2]);
+            let tetrahedron = Tetrahedron::new(p3[0], p3[1], p3[2], p3[3]);
+
+            let orientations_2d = edge.orientations(&p2) == p2.iter().map(|&p| Triangle::new(edge.p1, edge.p2, p).orientation()).collect::<Vec<_>>();
+            let orientations_3d = face.orientations(&p3) == p3.iter().map(|&p| Tetrahedron::new(face.p1, face.p2, face.p3, p).orientation()).collect::<Vec<_>>();
+            let in_circle = triangle.in_circle_tests(&p2) == p2.iter().map(|p| triangle.in_circle_test(p)).collect::<Vec<_>>();
+            let in_sphere = tetrahedron.in_circle_tests(&p3) == p3.iter().map(|p| tetrahedron.in_circle_test(p)).collect::<Vec<_>>();
+
+            TestResult::from_bool(orientations_2d && orientations_3d && in_circle && in_sphere)
+        }
+        quickcheck(batches_match_single_tests as fn(coordinates: Vec<(i8, i8, i8)>) -> TestResult)
+    }
+
+    #[test]
+    fn integer_and_single_precision_coordinates() {
+        let t = Triangle::new(Point2D::new(0, 0), Point2D::new(0, 1 << 30), Point2D::new(1 << 30, 1 << 30));
+
+        assert_eq!(t.orientation(), Some(Orientation::Positive));
+        assert_eq!(t.in_circle_test(&Point2D::new(1, 1)), Some(InCircleLocation::Inside));
+        assert_eq!(t.in_circle_test(&Point2D::new(1 << 30, 0)), Some(InCircleLocation::On));
+        assert_eq!(t.in_circle_test(&Point2D::new(-1, 0)), Some(InCircleLocation::Outside));
+        assert_eq!(Triangle::new(t.p1, t.p2, Point2D::new(0, -5)).try_orientation(), Err(GeometryError::Degenerate));
+
+        let big = 1i64 << 62;
+        let t = Tetrahedron::new(Point3D::new(0, 0, 0), Point3D::new(big, 0, 0), Point3D::new(0, big, 0), Point3D::new(0, 0, big));
+        assert_eq!(t.in_circle_test(&Point3D::new(big, big, 0)), Some(InCircleLocation::On));
+        assert_eq!(t.in_circle_test(&Point3D::new(big, big, 1)), Some(InCircleLocation::Inside));
+        assert_eq!(t.in_circle_test(&Point3D::new(big, big, -1)), Some(InCircleLocation::Outside));
+
+        let t = Triangle::new(Point2D::new(0.0f32, 0.0), Point2D::new(0.0, 1.0), Point2D::new(1.0, 1.0));
+        assert_eq!(t.in_circle_test(&Point2D::new(0.5, 0.5)), Some(InCircleLocation::Inside));
+        assert_eq!(t.try_in_circle_test(&Point2D::new(f32::NAN, 0.5)), Err(GeometryError::NotANumber));
+    }
+
+    #[test]
+    fn power_test() {
+        let w = |x, y, weight| Weighted::new(Point2D::new(x, y), weight);
+
+        // The circumcircle has its center at (0.5, 0.5) and a squared radius of 0.5
+        let t = Triangle::new(w(0.0, 0.0, 0.0), w(1.0, 0.0, 0.0), w(0.0, 1.0, 0.0));
+        assert_eq!(t.power_test(&w(0.5, 0.5, 0.0)), Some(InCircleLocation::Inside));
+        assert_eq!(t.power_test(&w(1.0, 1.0, 0.0)), Some(InCircleLocation::On));
+        assert_eq!(t.power_test(&w(1.0, 1.0, 0.25)), Some(InCircleLocation::Inside));
+        assert_eq!(t.power_test(&w(1.0, 1.0, -0.25)), Some(InCircleLocation::Outside));
+
+        // Heavier vertices shrink the orthogonal circle
+        let heavy = Triangle::new(w(0.0, 0.0, 0.25), w(1.0, 0.0, 0.25), w(0.0, 1.0, 0.25));
+        assert_eq!(heavy.power_test(&w(1.0, 1.0, 0.0)), Some(InCircleLocation::Outside));
+        assert_eq!(heavy.power_test(&w(1.0, 1.0, 0.25)), Some(InCircleLocation::On));
+        assert_eq!(Triangle::new(heavy.p2, heavy.p1, heavy.p3).power_test(&w(1.0, 1.0, 0.0)), Some(InCircleLocation::Outside));
+
+        assert_eq!(t.try_power_test(&w(0.5, 0.5, f64::NAN)), Err(GeometryError::NotANumber));
+        assert_eq!(Triangle::new(t.p1, t.p2, w(2.0, 0.0, 0.0)).try_power_test(&w(0.5, 0.5, 0.0)), Err(GeometryError::Degenerate));
+
+        let w = |x, y, z, weight| Weighted::new(Point3D::new(x, y, z), weight);
+        let t = Tetrahedron::new(w(0.0, 0.0, 0.0, 0.5), w(1.0, 0.0, 0.0, 0.5), w(0.0, 1.0, 0.0, 0.5), w(0.0, 0.0, 1.0, 0.5));
+        assert_eq!(t.power_test(&w(1.0, 1.0, 0.0, 0.5)), Some(InCircleLocation::On));
+        assert_eq!(t.power_test(&w(1.0, 1.0, 0.0, 0.0)), Some(InCircleLocation::Outside));
+        assert_eq!(t.power_test(&w(0.4, 0.4, 0.4, 0.0)), Some(InCircleLocation::Inside));
+    }
+
+    #[test]
+    fn higher_dimensions() {
+        // The simplex of the origin and the unit vectors, with its circumcenter at (0.5, ..., 0.5)
+        let unit = |i: usize| { let mut p = [0.0; 4]; if i < 4 { p[i] = 1.0; } p };
+        let simplex = (0..5).map(unit).collect::<Vec<_>>();
+        assert_eq!(orient(&simplex), Some(Orientation::Negative));
+        let mut swapped = simplex.clone();
+        swapped.swap(0, 1);
+        assert_eq!(orient(&swapped), Some(Orientation::Positive));
+
+        for s in &[&simplex, &swapped] {
+            assert_eq!(insphere(s, &[0.5; 4]), Some(InCircleLocation::Inside));
+            assert_eq!(insphere(s, &[1.0, 1.0, 0.0, 0.0]), Some(InCircleLocation::On));
+            assert_eq!(insphere(s, &[1.0, 1.0, 1.0, 1.0]), Some(InCircleLocation::On));
+            assert_eq!(insphere(s, &[1.0, 1.0, 1.0, 1.1]), Some(InCircleLocation::Outside));
+        }
+
+        // Exact: the corners of a box with rounded differences are cospherical
+        let corner = |i: usize| [[0.1, 0.7][i & 1], [0.2, 0.9][(i >> 1) & 1], [0.3, 0.8][(i >> 2) & 1], [0.4, 0.6][(i >> 3) & 1], 0.5];
+        let degenerate = [corner(0), corner(1), corner(2), corner(4), corner(8), corner(15)];
+        let box_simplex = [corner(0), corner(1), corner(2), corner(4), corner(8), [0.1, 0.2, 0.3, 0.4, 0.9]];
+        assert_eq!(orient(&degenerate), None);
+        assert_eq!(try_orient(&degenerate), Err(GeometryError::Degenerate));
+        assert_eq!(insphere(&box_simplex[..5].iter().map(|p| [p[0], p[1], p[2], p[3]]).collect::<Vec<_>>(), &[0.7, 0.9, 0.8, 0.6]),
+                   Some(InCircleLocation::On));
+        assert!(orient(&box_simplex).is_some());
+        assert_eq!(insphere(&box_simplex, &[0.7, 0.9, 0.8, 0.6, 0.9]), Some(InCircleLocation::On));
+        assert_eq!(insphere(&box_simplex, &[0.7, 0.9, 0.8, 0.6, 0.5]), Some(InCircleLocation::On));
+        assert_eq!(insphere(&box_simplex, &[0.4, 0.5, 0.5, 0.5, 0.7]), Some(InCircleLocation::Inside));
+
+        assert_eq!(insphere(&simplex, &[f64::NAN; 4]), None);
+        assert_eq!(try_insphere(&simplex, &[0.0, f64::INFINITY, 0.0, 0.0]), Err(GeometryError::Infinite));
+        assert_eq!(try_orient(&[[f64::NAN, 0.0], [1.0, 0.0], [0.0, 1.0]]), Err(GeometryError::NotANumber));
+    }
+
+    #[test]
+    fn higher_dimensions_agree_with_the_plane_and_space() {
+        fn agree_2d(c: Vec<(i8, i8)>) -> TestResult {
+            if c.len() < 4 {
+                return TestResult::discard();
+            }
+
+            // Coordinates on a coarse grid, scaled so their differences round
+            let p = c.iter().take(4).map(|&(x, y)| [f64::from(x % 4) * 0.1, f64::from(y % 4) * 0.1]).collect::<Vec<_>>();
+            let q = p.iter().map(|p| Point2D::new(p[0], p[1])).collect::<Vec<_>>();
+            let t = Triangle::new(q[0], q[1], q[2]);
+
+            TestResult::from_bool(orient(&p[..3]) == t.orientation() && insphere(&p[..3], &p[3]) == t.in_circle_test(&q[3]))
+        }
+        quickcheck(agree_2d as fn(c: Vec<(i8, i8)>) -> TestResult);
+
+        fn agree_3d(c: Vec<(i8, i8, i8)>) -> TestResult {
+            if c.len() < 5 {
+                return TestResult::discard();
+            }
+
+            let p = c.iter().take(5).map(|&(x, y, z)| [f64::from(x % 3) * 0.1, f64::from(y % 3) * 0.1, f64::from(z % 3) * 0.1]).collect::<Vec<_>>();
+            let q = p.iter().map(|p| Point3D::new(p[0], p[1], p[2])).collect::<Vec<_>>();
+            let t = Tetrahedron::new(q[0], q[1], q[2], q[3]);
+
+            TestResult::from_bool(orient(&p[..4]) == t.orientation() && insphere(&p[..4], &p[4]) == t.in_circle_test(&q[4]))
+        }
+        quickcheck(agree_3d as fn(c: Vec<(i8, i8, i8)>) -> TestResult);
+    }
 }
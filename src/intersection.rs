@@ -0,0 +1,213 @@
+//! Intersections of segments, classified exactly with the orientation predicate.
+
+use geometry::*;
+use coordinate::Coordinate;
+use expansion::Expansion;
+
+use std::cmp::Ordering;
+
+/// How two segments intersect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection<P> {
+    /// The segments have no point in common.
+    Disjoint,
+    /// The segments cross in a single point, which is not an end point of either.
+    Proper,
+    /// The segments meet in a single point, which is an end point of at least one of them.
+    Touching(P),
+    /// The segments are collinear and share the part between the end points of the edge, which
+    /// runs in the direction of the first segment.
+    Overlap(Edge<P>),
+}
+
+fn sign(det: f64) -> Option<Ordering> {
+    det.partial_cmp(&0.0)
+}
+
+impl<T: Coordinate + PartialOrd> Edge<Point2D<T>> {
+    /// Classifies the intersection with another segment. Returns `None` if a segment is
+    /// degenerate, or a coordinate is NaN.
+    pub fn intersect(&self, other: &Edge<Point2D<T>>) -> Option<SegmentIntersection<Point2D<T>>> {
+        let (a, b, c, d) = (&self.p1, &self.p2, &other.p1, &other.p2);
+        if a == b || c == d {
+            return None;
+        }
+
+        let abc = sign(T::orient2d(a, b, c))?;
+        let abd = sign(T::orient2d(a, b, d))?;
+        let cda = sign(T::orient2d(c, d, a))?;
+        let cdb = sign(T::orient2d(c, d, b))?;
+
+        let separated = |s: Ordering, t: Ordering| s == t && s != Ordering::Equal;
+        if separated(abc, abd) || separated(cda, cdb) {
+            return Some(SegmentIntersection::Disjoint);
+        }
+
+        if abc == Ordering::Equal && abd == Ordering::Equal {
+            return Some(collinear_intersection(a, b, c, d));
+        }
+
+        Some(match (abc, abd, cda, cdb) {
+            (Ordering::Equal, _, _, _) => SegmentIntersection::Touching(*c),
+            (_, Ordering::Equal, _, _) => SegmentIntersection::Touching(*d),
+            (_, _, Ordering::Equal, _) => SegmentIntersection::Touching(*a),
+            (_, _, _, Ordering::Equal) => SegmentIntersection::Touching(*b),
+            _ => SegmentIntersection::Proper,
+        })
+    }
+}
+
+/// The intersection of collinear segments, by comparing the end points along the first one.
+fn collinear_intersection<T: Coordinate + PartialOrd>(a: &Point2D<T>, b: &Point2D<T>, c: &Point2D<T>, d: &Point2D<T>) -> SegmentIntersection<Point2D<T>> {
+    // Along a coordinate in which the segment is not constant, the order of collinear points is
+    // their order on the line, reversed if the segment decreases in it
+    let along = |p: &Point2D<T>, q: &Point2D<T>| {
+        let ordering = if a.x != b.x { p.x.partial_cmp(&q.x) } else { p.y.partial_cmp(&q.y) };
+        let ordering = ordering.unwrap();
+        if a.x > b.x || (a.x == b.x && a.y > b.y) { ordering.reverse() } else { ordering }
+    };
+
+    let (c, d) = if along(c, d) == Ordering::Greater { (d, c) } else { (c, d) };
+    let start = if along(a, c) == Ordering::Less { c } else { a };
+    let end = if along(b, d) == Ordering::Greater { d } else { b };
+
+    match along(start, end) {
+        Ordering::Less => SegmentIntersection::Overlap(Edge::new(*start, *end)),
+        Ordering::Equal => SegmentIntersection::Touching(*start),
+        Ordering::Greater => SegmentIntersection::Disjoint,
+    }
+}
+
+/// The exact value of `orient2d(a, b, c)`.
+fn orient2d_exact(a: &Point2D, b: &Point2D, c: &Point2D) -> Expansion {
+    let (acx, acy) = (Expansion::difference(a.x, c.x), Expansion::difference(a.y, c.y));
+    let (bcx, bcy) = (Expansion::difference(b.x, c.x), Expansion::difference(b.y, c.y));
+
+    &acx * &bcy - &acy * &bcx
+}
+
+/// The squared length, rounded.
+fn length_squared(e: &Edge<Point2D>) -> f64 {
+    let (dx, dy) = (e.p2.x - e.p1.x, e.p2.y - e.p1.y);
+    dx * dx + dy * dy
+}
+
+impl Edge<Point2D> {
+    /// The point in which the segments meet, or `None` if they are disjoint or overlap in more
+    /// than a point, or `intersect` returns `None`.
+    ///
+    /// End points are returned exactly. A proper crossing is interpolated along the shorter
+    /// segment `c`, `d` at the ratio of the exact distances of `c` and `d` to the other line, so
+    /// each coordinate is within `5 * f64::EPSILON * max(|d - c|, |x|)` of the exact
+    /// intersection `x`.
+    pub fn intersection_point(&self, other: &Edge<Point2D>) -> Option<Point2D> {
+        match self.intersect(other)? {
+            SegmentIntersection::Touching(p) => Some(p),
+            SegmentIntersection::Proper => {
+                let (long, short) = if length_squared(self) >= length_squared(other) { (self, other) } else { (other, self) };
+
+                // The distances have opposite signs, so their difference does not cancel
+                let dc = orient2d_exact(&long.p1, &long.p2, &short.p1).approximate();
+                let dd = orient2d_exact(&long.p1, &long.p2, &short.p2).approximate();
+                let t = dc / (dc - dd);
+
+                let (c, d) = (short.p1, short.p2);
+                Some(Point2D::new(c.x + t * (d.x - c.x), c.y + t * (d.y - c.y)))
+            },
+            SegmentIntersection::Disjoint | SegmentIntersection::Overlap(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn segment(a: (f64, f64), b: (f64, f64)) -> Edge<Point2D> {
+        Edge::new(Point2D::new(a.0, a.1), Point2D::new(b.0, b.1))
+    }
+
+    #[test]
+    fn classification() {
+        let ab = segment((0.0, 0.0), (2.0, 2.0));
+
+        assert_eq!(ab.intersect(&segment((0.0, 2.0), (2.0, 0.0))), Some(SegmentIntersection::Proper));
+        assert_eq!(ab.intersect(&segment((0.0, 1.0), (1.0, 2.0))), Some(SegmentIntersection::Disjoint));
+        assert_eq!(ab.intersect(&segment((3.0, 3.0), (4.0, 4.0))), Some(SegmentIntersection::Disjoint));
+        assert_eq!(ab.intersect(&segment((1.0, 1.0), (1.0, 5.0))), Some(SegmentIntersection::Touching(Point2D::new(1.0, 1.0))));
+        assert_eq!(ab.intersect(&segment((2.0, 2.0), (3.0, 0.0))), Some(SegmentIntersection::Touching(Point2D::new(2.0, 2.0))));
+        assert_eq!(ab.intersect(&segment((2.0, 2.0), (3.0, 3.0))), Some(SegmentIntersection::Touching(Point2D::new(2.0, 2.0))));
+        assert_eq!(ab.intersect(&segment((3.0, 3.0), (1.0, 1.0))), Some(SegmentIntersection::Overlap(segment((1.0, 1.0), (2.0, 2.0)))));
+        assert_eq!(ab.intersect(&segment((-1.0, -1.0), (3.0, 3.0))), Some(SegmentIntersection::Overlap(ab)));
+        assert_eq!(segment((2.0, 2.0), (0.0, 0.0)).intersect(&segment((1.0, 1.0), (3.0, 3.0))),
+                   Some(SegmentIntersection::Overlap(segment((2.0, 2.0), (1.0, 1.0)))));
+        assert_eq!(segment((0.0, 0.0), (0.0, 2.0)).intersect(&segment((0.0, 3.0), (0.0, 1.0))),
+                   Some(SegmentIntersection::Overlap(segment((0.0, 1.0), (0.0, 2.0)))));
+
+        assert_eq!(ab.intersect(&segment((1.0, 1.0), (1.0, 1.0))), None);
+        assert_eq!(ab.intersect(&segment((f64::NAN, 1.0), (1.0, 1.0))), None);
+
+        // The float closest to 0.1 is slightly more than a third of the one closest to 0.3, so
+        // (0.3, 0.1) lies just above the line through the origin and (3, 1)
+        let e = segment((0.0, 0.0), (3.0, 1.0));
+        assert_eq!(e.intersect(&segment((0.3, 0.1), (0.3, 5.0))), Some(SegmentIntersection::Disjoint));
+        assert_eq!(e.intersect(&segment((0.3, 0.1), (0.3, -5.0))), Some(SegmentIntersection::Proper));
+        assert_eq!(segment((0.25, 0.25), (0.75, 0.75)).intersect(&segment((0.5, 0.5), (1.0, 0.0))),
+                   Some(SegmentIntersection::Touching(Point2D::new(0.5, 0.5))));
+
+        let e = Edge::new(Point2D::new(i64::MIN, i64::MIN), Point2D::new(i64::MAX, i64::MAX));
+        assert_eq!(e.intersect(&Edge::new(Point2D::new(-1, 1), Point2D::new(1, -1))), Some(SegmentIntersection::Proper));
+        assert_eq!(e.intersect(&Edge::new(Point2D::new(0, 0), Point2D::new(1, -1))), Some(SegmentIntersection::Touching(Point2D::new(0, 0))));
+    }
+
+    #[test]
+    fn classification_is_symmetric() {
+        fn classification_is_symmetric(c: Vec<(i8, i8)>) -> TestResult {
+            if c.len() < 4 {
+                return TestResult::discard();
+            }
+
+            // A small grid has many touching and collinear segments
+            let p = c.iter().map(|&(x, y)| Point2D::new((x % 3) as i32, (y % 3) as i32)).collect::<Vec<_>>();
+            let (e, f) = (Edge::new(p[0], p[1]), Edge::new(p[2], p[3]));
+            let (ef, fe) = match (e.intersect(&f), f.intersect(&e)) {
+                (Some(ef), Some(fe)) => (ef, fe),
+                (ef, fe) => return TestResult::from_bool(ef.is_none() && fe.is_none()),
+            };
+
+            TestResult::from_bool(match (ef, fe) {
+                (SegmentIntersection::Overlap(x), SegmentIntersection::Overlap(y)) => x == y || x == Edge::new(y.p2, y.p1),
+                (SegmentIntersection::Touching(x), SegmentIntersection::Touching(y)) => x == y,
+                (x, y) => x == y,
+            })
+        }
+        quickcheck(classification_is_symmetric as fn(c: Vec<(i8, i8)>) -> TestResult)
+    }
+
+    #[test]
+    fn intersection_points_have_bounded_error() {
+        fn intersection_points_have_bounded_error(a: (i32, i32), b: (i32, i32), c: (i32, i32), d: (i32, i32)) -> TestResult {
+            let p = |p: (i32, i32)| Point2D::new(p.0 as f64, p.1 as f64);
+            let (e, f) = (Edge::new(p(a), p(b)), Edge::new(p(c), p(d)));
+            let x = match e.intersection_point(&f) {
+                Some(x) => x,
+                None => return TestResult::discard(),
+            };
+
+            // The exact intersection a + t (b - a), with t = num / den
+            let w = |x: i32| x as i128;
+            let num = (w(c.0) - w(a.0)) * (w(d.1) - w(c.1)) - (w(c.1) - w(a.1)) * (w(d.0) - w(c.0));
+            let den = (w(b.0) - w(a.0)) * (w(d.1) - w(c.1)) - (w(b.1) - w(a.1)) * (w(d.0) - w(c.0));
+            let exact = |a: i32, b: i32| a as f64 + (num as f64 / den as f64) * (b as f64 - a as f64);
+
+            let scale = |e: &Edge<Point2D>| (e.p2.x - e.p1.x).abs().max((e.p2.y - e.p1.y).abs());
+            let bound = 6.0 * f64::EPSILON * scale(&e).max(scale(&f)).max(x.x.abs()).max(x.y.abs());
+
+            // The reference is rounded itself, hence the extra slack in the bound
+            TestResult::from_bool((x.x - exact(a.0, b.0)).abs() <= bound && (x.y - exact(a.1, b.1)).abs() <= bound)
+        }
+        quickcheck(intersection_points_have_bounded_error as fn(a: (i32, i32), b: (i32, i32), c: (i32, i32), d: (i32, i32)) -> TestResult)
+    }
+}
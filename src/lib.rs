@@ -1,14 +1,33 @@
 #![cfg_attr(feature="clippy", feature(plugin))]
 #![cfg_attr(feature="clippy", plugin(clippy))]
 
-extern crate libc;
-
 #[cfg(test)] extern crate quickcheck;
 #[cfg(test)] extern crate rand;
 
 pub use in_circle::*;
 pub use geometry::*;
+pub use error::*;
+pub use coordinate::Coordinate;
+pub use intersection::*;
+pub use polygon::*;
 
 pub mod in_circle;
 pub mod geometry;
+pub mod error;
+pub mod coordinate;
+pub mod intersection;
+pub mod polygon;
+pub mod expansion;
+pub mod sos;
+#[cfg(feature="rational")] pub mod rational;
+#[cfg(feature="statistics")] pub mod statistics;
+pub mod dcel;
 pub mod delaunay;
+pub mod hierarchy;
+pub mod delaunay3d;
+pub mod voronoi;
+pub mod voronoi3d;
+
+mod predicates;
+mod random;
+
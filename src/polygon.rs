@@ -0,0 +1,196 @@
+//! Point location in polygons, with the winding number computed exactly from the orientation
+//! predicate, so it agrees with the triangulations on which side of an edge a point lies.
+
+use geometry::*;
+use error::GeometryError;
+use coordinate::Coordinate;
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonLocation {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+/// A closed polygon, with an edge from each vertex to the next one and from the last to the first.
+/// It may be self-intersecting, and its vertices may repeat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon<P> {
+    pub vertices: Vec<P>,
+}
+
+impl<P: Copy> Polygon<P> {
+    pub fn new(vertices: Vec<P>) -> Polygon<P> {
+        Polygon {
+            vertices,
+        }
+    }
+
+    pub fn edges(&self) -> Vec<Edge<P>> {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().zip(next).map(|(&p1, &p2)| Edge::new(p1, p2)).collect()
+    }
+}
+
+impl<P: Validate> Validate for Polygon<P> {
+    fn validate(&self) -> Result<(), GeometryError> {
+        self.vertices.iter().try_for_each(Validate::validate)
+    }
+}
+
+/// Where `p` lies relative to the line through an edge, and whether it lies on the edge itself.
+fn edge_side<T: Coordinate + PartialOrd>(e: &Edge<Point2D<T>>, p: &Point2D<T>) -> Option<(Ordering, bool)> {
+    let side = T::orient2d(&e.p1, &e.p2, p).partial_cmp(&0.0)?;
+    let between = |a: &T, b: &T, x: &T| (a <= x && x <= b) || (b <= x && x <= a);
+    let on_edge = side == Ordering::Equal && between(&e.p1.x, &e.p2.x, &p.x) && between(&e.p1.y, &e.p2.y, &p.y);
+
+    Some((side, on_edge))
+}
+
+impl<T: Coordinate + PartialOrd> Polygon<Point2D<T>> {
+    /// The number of times the boundary winds counter-clockwise around the point. Returns `None`
+    /// if the point lies on the boundary, or a coordinate is NaN.
+    pub fn winding_number(&self, point: &Point2D<T>) -> Option<i32> {
+        let mut winding = 0;
+
+        for e in self.edges() {
+            let (side, on_edge) = edge_side(&e, point)?;
+            if on_edge {
+                return None;
+            }
+
+            // Count the edges crossing the horizontal ray to the right of the point, upwards if
+            // it is left of them and downwards if it is right of them
+            if e.p1.y <= point.y && e.p2.y > point.y && side == Ordering::Greater {
+                winding += 1;
+            } else if e.p1.y > point.y && e.p2.y <= point.y && side == Ordering::Less {
+                winding -= 1;
+            }
+        }
+
+        Some(winding)
+    }
+
+    /// Locates the point by the non-zero winding rule, so the overlapping parts of a
+    /// self-intersecting polygon are inside. Returns `None` if a coordinate is NaN.
+    pub fn locate(&self, point: &Point2D<T>) -> Option<PolygonLocation> {
+        let mut boundary = false;
+        for e in self.edges() {
+            boundary |= edge_side(&e, point)?.1;
+        }
+
+        if boundary {
+            return Some(PolygonLocation::Boundary);
+        }
+
+        Some(match self.winding_number(point)? {
+            0 => PolygonLocation::Outside,
+            _ => PolygonLocation::Inside,
+        })
+    }
+
+    /// Like `locate`, but tells why the test failed.
+    pub fn try_locate(&self, point: &Point2D<T>) -> Result<PolygonLocation, GeometryError> {
+        self.validate()?;
+        point.validate()?;
+
+        self.locate(point).ok_or(GeometryError::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use in_circle::Orientable;
+    use quickcheck::{TestResult, quickcheck};
+
+    fn polygon(vertices: &[(f64, f64)]) -> Polygon<Point2D> {
+        Polygon::new(vertices.iter().map(|&(x, y)| Point2D::new(x, y)).collect())
+    }
+
+    #[test]
+    fn simple_polygons() {
+        let square = polygon(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        let locate = |x, y| square.locate(&Point2D::new(x, y)).unwrap();
+
+        assert_eq!(locate(1.0, 1.0), PolygonLocation::Inside);
+        assert_eq!(locate(3.0, 1.0), PolygonLocation::Outside);
+        assert_eq!(locate(-1.0, 0.0), PolygonLocation::Outside);
+        assert_eq!(locate(3.0, 2.0), PolygonLocation::Outside);
+        assert_eq!(locate(1.0, 0.0), PolygonLocation::Boundary);
+        assert_eq!(locate(2.0, 2.0), PolygonLocation::Boundary);
+        assert_eq!(locate(0.0, 1.5), PolygonLocation::Boundary);
+
+        assert_eq!(square.winding_number(&Point2D::new(1.0, 1.0)), Some(1));
+        let reversed = Polygon::new(square.vertices.iter().rev().cloned().collect());
+        assert_eq!(reversed.winding_number(&Point2D::new(1.0, 1.0)), Some(-1));
+        assert_eq!(reversed.winding_number(&Point2D::new(1.0, 0.0)), None);
+
+        // A vertex at the height of the point, where the ray passes through the polygon
+        let diamond = polygon(&[(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)]);
+        assert_eq!(diamond.locate(&Point2D::new(0.0, 0.0)), Some(PolygonLocation::Inside));
+        assert_eq!(diamond.locate(&Point2D::new(-2.0, 0.0)), Some(PolygonLocation::Outside));
+
+        // Exact: the point is just above the line through the origin and (3, 1)
+        let triangle = polygon(&[(0.0, 0.0), (3.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(triangle.locate(&Point2D::new(0.3, 0.1)), Some(PolygonLocation::Inside));
+        assert_eq!(triangle.locate(&Point2D::new(1.5, 0.5)), Some(PolygonLocation::Boundary));
+
+        assert_eq!(square.locate(&Point2D::new(f64::NAN, 1.0)), None);
+        assert_eq!(square.try_locate(&Point2D::new(f64::NAN, 1.0)), Err(GeometryError::NotANumber));
+        assert_eq!(polygon(&[]).locate(&Point2D::new(0.0, 0.0)), Some(PolygonLocation::Outside));
+    }
+
+    #[test]
+    fn self_intersecting_polygons() {
+        // A pentagram winds twice around its center
+        let star = Polygon::new((0..5).map(|i| {
+            let angle = (i * 2) as f64 * 2.0 * ::std::f64::consts::PI / 5.0;
+            Point2D::new(angle.cos(), angle.sin())
+        }).collect());
+        assert_eq!(star.winding_number(&Point2D::new(0.0, 0.0)), Some(2));
+        assert_eq!(star.locate(&Point2D::new(0.0, 0.0)), Some(PolygonLocation::Inside));
+        assert_eq!(star.locate(&Point2D::new(0.9, 0.0)), Some(PolygonLocation::Inside));
+        assert_eq!(star.locate(&Point2D::new(0.0, 0.9)), Some(PolygonLocation::Outside));
+
+        // A bow tie, with the loops in opposite directions
+        let bow_tie = Polygon::new(vec!(Point2D::new(0, 0), Point2D::new(2, 2), Point2D::new(2, 0), Point2D::new(0, 2)));
+        assert_eq!(bow_tie.winding_number(&Point2D::new(1, 0)), Some(0));
+        assert_eq!(bow_tie.locate(&Point2D::new(1, 1)), Some(PolygonLocation::Boundary));
+        assert_eq!(bow_tie.winding_number(&Point2D::new(0, 1)), None);
+        let wide = |p: Point2D<i32>| Point2D::new(i64::from(p.x) * 4, i64::from(p.y) * 4);
+        let bow_tie = Polygon::new(bow_tie.vertices.iter().map(|&p| wide(p)).collect());
+        assert_eq!(bow_tie.winding_number(&Point2D::new(1, 4)), Some(1));
+        assert_eq!(bow_tie.winding_number(&Point2D::new(7, 4)), Some(-1));
+        assert_eq!(bow_tie.winding_number(&Point2D::new(4, 1)), Some(0));
+    }
+
+    #[test]
+    fn triangles_agree_with_the_orientation() {
+        fn triangles_agree_with_the_orientation(a: (i16, i16), b: (i16, i16), c: (i16, i16), p: (i16, i16)) -> TestResult {
+            let q = |p: (i16, i16)| Point2D::new(i32::from(p.0 % 8), i32::from(p.1 % 8));
+            let t = Triangle::new(q(a), q(b), q(c));
+            let p = q(p);
+            let orientation = match t.orientation() {
+                Some(o) => o,
+                None => return TestResult::discard(),
+            };
+
+            // Inside a triangle means strictly left of every edge in the winding direction
+            let sides = t.edges().iter().map(|e| i32::orient2d(&e.p1, &e.p2, &p) * orientation.to_f64_multiplier()).collect::<Vec<_>>();
+            let expected = if sides.iter().all(|&s| s < 0.0) {
+                PolygonLocation::Inside
+            } else if sides.iter().any(|&s| s > 0.0) {
+                PolygonLocation::Outside
+            } else {
+                PolygonLocation::Boundary
+            };
+
+            TestResult::from_bool(Polygon::new(vec!(t.p1, t.p2, t.p3)).locate(&p) == Some(expected))
+        }
+        quickcheck(triangles_agree_with_the_orientation as fn(a: (i16, i16), b: (i16, i16), c: (i16, i16), p: (i16, i16)) -> TestResult)
+    }
+}
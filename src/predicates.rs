@@ -0,0 +1,1626 @@
+//! Adaptive precision floating-point predicates, ported from Jonathan Richard Shewchuk's
+//! `predicates.c`.
+//!
+//! Each predicate first evaluates its determinant with plain floating-point arithmetic and an
+//! error bound. Only if the sign is uncertain, it falls back to increasingly precise stages,
+//! computing the exact result as an expansion, a sum of non-overlapping floating-point numbers,
+//! in the worst case. The results are identical to those of the C implementation.
+
+use geometry::*;
+use expansion::{Expansion, determinant};
+
+use std::mem;
+
+/// Counts evaluations that finish in the given stage, if the `statistics` feature is enabled,
+/// and compiles to nothing otherwise.
+macro_rules! record {
+    ($predicate:ident, $stage:ident) => { record!($predicate, $stage, 1) };
+    ($predicate:ident, $stage:ident, $count:expr) => {{
+        #[cfg(feature = "statistics")]
+        ::statistics::record(::statistics::Predicate::$predicate, ::statistics::Stage::$stage, $count);
+        #[cfg(not(feature = "statistics"))]
+        let _ = $count;
+    }};
+}
+
+/// Half the distance from 1.0 to the next float, the largest power of two for which
+/// `1.0 + EPSILON` rounds to 1.0.
+const EPSILON: f64 = f64::EPSILON / 2.0;
+/// `2^ceil(p / 2) + 1` for the precision `p` of a float, to split it into halves that can be
+/// multiplied without rounding.
+const SPLITTER: f64 = 134_217_729.0;
+
+// The coefficients of the error bounds of the stages of the predicates, from Shewchuk's
+// `exactinit`. They only depend on the floating-point format, so they are constants.
+const RESULTERRBOUND: f64 = (3.0 + 8.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_B: f64 = (2.0 + 12.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_C: f64 = (9.0 + 64.0 * EPSILON) * EPSILON * EPSILON;
+const O3DERRBOUND_A: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const O3DERRBOUND_B: f64 = (3.0 + 28.0 * EPSILON) * EPSILON;
+const O3DERRBOUND_C: f64 = (26.0 + 288.0 * EPSILON) * EPSILON * EPSILON;
+const ICCERRBOUND_A: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+const ICCERRBOUND_B: f64 = (4.0 + 48.0 * EPSILON) * EPSILON;
+const ICCERRBOUND_C: f64 = (44.0 + 576.0 * EPSILON) * EPSILON * EPSILON;
+const ISPERRBOUND_A: f64 = (16.0 + 224.0 * EPSILON) * EPSILON;
+const ISPERRBOUND_B: f64 = (5.0 + 72.0 * EPSILON) * EPSILON;
+const ISPERRBOUND_C: f64 = (71.0 + 1408.0 * EPSILON) * EPSILON * EPSILON;
+// One rounding deeper than the in-circle tests, for subtracting the weights
+const PW2ERRBOUND_A: f64 = (11.0 + 128.0 * EPSILON) * EPSILON;
+const PW3ERRBOUND_A: f64 = (17.0 + 256.0 * EPSILON) * EPSILON;
+
+// The basic operations return the approximate result first and its roundoff error second.
+
+#[inline]
+pub(crate) fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bvirt = x - a;
+    (x, b - bvirt)
+}
+
+#[inline]
+pub(crate) fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bvirt = x - a;
+    let avirt = x - bvirt;
+    let bround = b - bvirt;
+    let around = a - avirt;
+    (x, around + bround)
+}
+
+#[inline]
+fn two_diff_tail(a: f64, b: f64, x: f64) -> f64 {
+    let bvirt = a - x;
+    let avirt = x + bvirt;
+    let bround = bvirt - b;
+    let around = a - avirt;
+    around + bround
+}
+
+#[inline]
+pub(crate) fn two_diff(a: f64, b: f64) -> (f64, f64) {
+    let x = a - b;
+    (x, two_diff_tail(a, b, x))
+}
+
+#[inline]
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let abig = c - a;
+    let ahi = c - abig;
+    (ahi, a - ahi)
+}
+
+#[inline]
+fn two_product_presplit(a: f64, b: f64, bhi: f64, blo: f64) -> (f64, f64) {
+    let x = a * b;
+    let (ahi, alo) = split(a);
+    let err1 = x - (ahi * bhi);
+    let err2 = err1 - (alo * bhi);
+    let err3 = err2 - (ahi * blo);
+    (x, (alo * blo) - err3)
+}
+
+#[inline]
+pub(crate) fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let (bhi, blo) = split(b);
+    two_product_presplit(a, b, bhi, blo)
+}
+
+#[inline]
+fn square(a: f64) -> (f64, f64) {
+    let x = a * a;
+    let (ahi, alo) = split(a);
+    let err1 = x - (ahi * ahi);
+    let err3 = err1 - ((ahi + ahi) * alo);
+    (x, (alo * alo) - err3)
+}
+
+// The fixed length sums and products return their components from the smallest to the largest,
+// like an expansion.
+
+#[inline]
+fn two_one_sum(a1: f64, a0: f64, b: f64) -> [f64; 3] {
+    let (i, x0) = two_sum(a0, b);
+    let (x2, x1) = two_sum(a1, i);
+    [x0, x1, x2]
+}
+
+#[inline]
+fn two_one_diff(a1: f64, a0: f64, b: f64) -> [f64; 3] {
+    let (i, x0) = two_diff(a0, b);
+    let (x2, x1) = two_sum(a1, i);
+    [x0, x1, x2]
+}
+
+#[inline]
+fn two_two_sum((a1, a0): (f64, f64), (b1, b0): (f64, f64)) -> [f64; 4] {
+    let [x0, z0, j] = two_one_sum(a1, a0, b0);
+    let [x1, x2, x3] = two_one_sum(j, z0, b1);
+    [x0, x1, x2, x3]
+}
+
+#[inline]
+fn two_two_diff((a1, a0): (f64, f64), (b1, b0): (f64, f64)) -> [f64; 4] {
+    let [x0, z0, j] = two_one_diff(a1, a0, b0);
+    let [x1, x2, x3] = two_one_diff(j, z0, b1);
+    [x0, x1, x2, x3]
+}
+
+#[inline]
+fn two_one_product((a1, a0): (f64, f64), b: f64) -> [f64; 4] {
+    let (bhi, blo) = split(b);
+    let (i, x0) = two_product_presplit(a0, b, bhi, blo);
+    let (j, z0) = two_product_presplit(a1, b, bhi, blo);
+    let (k, x1) = two_sum(i, z0);
+    let (x3, x2) = fast_two_sum(j, k);
+    [x0, x1, x2, x3]
+}
+
+/// Sets `h = e + f` and returns the length of `h`. Zero components are eliminated, and the
+/// result is strongly non-overlapping if `e` and `f` are.
+pub(crate) fn fast_expansion_sum_zeroelim(e: &[f64], f: &[f64], h: &mut [f64]) -> usize {
+    // Components past the end are read but never used, like in the C implementation
+    let at = |s: &[f64], i: usize| if i < s.len() { s[i] } else { 0.0 };
+
+    let mut enow = e[0];
+    let mut fnow = f[0];
+    let (mut eindex, mut findex) = (0, 0);
+    let mut q;
+
+    if (fnow > enow) == (fnow > -enow) {
+        q = enow;
+        eindex += 1;
+        enow = at(e, eindex);
+    } else {
+        q = fnow;
+        findex += 1;
+        fnow = at(f, findex);
+    }
+
+    let mut hindex = 0;
+
+    if eindex < e.len() && findex < f.len() {
+        let (qnew, hh) = if (fnow > enow) == (fnow > -enow) {
+            let sum = fast_two_sum(enow, q);
+            eindex += 1;
+            enow = at(e, eindex);
+            sum
+        } else {
+            let sum = fast_two_sum(fnow, q);
+            findex += 1;
+            fnow = at(f, findex);
+            sum
+        };
+        q = qnew;
+        if hh != 0.0 {
+            h[hindex] = hh;
+            hindex += 1;
+        }
+
+        while eindex < e.len() && findex < f.len() {
+            let (qnew, hh) = if (fnow > enow) == (fnow > -enow) {
+                let sum = two_sum(q, enow);
+                eindex += 1;
+                enow = at(e, eindex);
+                sum
+            } else {
+                let sum = two_sum(q, fnow);
+                findex += 1;
+                fnow = at(f, findex);
+                sum
+            };
+            q = qnew;
+            if hh != 0.0 {
+                h[hindex] = hh;
+                hindex += 1;
+            }
+        }
+    }
+
+    while eindex < e.len() {
+        let (qnew, hh) = two_sum(q, enow);
+        eindex += 1;
+        enow = at(e, eindex);
+        q = qnew;
+        if hh != 0.0 {
+            h[hindex] = hh;
+            hindex += 1;
+        }
+    }
+
+    while findex < f.len() {
+        let (qnew, hh) = two_sum(q, fnow);
+        findex += 1;
+        fnow = at(f, findex);
+        q = qnew;
+        if hh != 0.0 {
+            h[hindex] = hh;
+            hindex += 1;
+        }
+    }
+
+    if q != 0.0 || hindex == 0 {
+        h[hindex] = q;
+        hindex += 1;
+    }
+
+    hindex
+}
+
+/// Sets `h = b * e` and returns the length of `h`, eliminating zero components.
+pub(crate) fn scale_expansion_zeroelim(e: &[f64], b: f64, h: &mut [f64]) -> usize {
+    let (bhi, blo) = split(b);
+    let (mut q, hh) = two_product_presplit(e[0], b, bhi, blo);
+    let mut hindex = 0;
+
+    if hh != 0.0 {
+        h[hindex] = hh;
+        hindex += 1;
+    }
+
+    for &enow in &e[1..] {
+        let (product1, product0) = two_product_presplit(enow, b, bhi, blo);
+        let (sum, hh) = two_sum(q, product0);
+        if hh != 0.0 {
+            h[hindex] = hh;
+            hindex += 1;
+        }
+        let (qnew, hh) = fast_two_sum(product1, sum);
+        q = qnew;
+        if hh != 0.0 {
+            h[hindex] = hh;
+            hindex += 1;
+        }
+    }
+
+    if q != 0.0 || hindex == 0 {
+        h[hindex] = q;
+        hindex += 1;
+    }
+
+    hindex
+}
+
+/// A one-word approximation of an expansion.
+pub(crate) fn estimate(e: &[f64]) -> f64 {
+    e[1..].iter().fold(e[0], |q, &c| q + c)
+}
+
+fn scale(e: &[f64], b: f64, h: &mut [f64]) -> usize {
+    scale_expansion_zeroelim(e, b, h)
+}
+
+fn sum(e: &[f64], f: &[f64], h: &mut [f64]) -> usize {
+    fast_expansion_sum_zeroelim(e, f, h)
+}
+
+/// A running sum, alternating between two buffers.
+struct Accumulator<'a> {
+    now: &'a mut [f64],
+    other: &'a mut [f64],
+    len: usize,
+}
+
+impl<'a> Accumulator<'a> {
+    fn add(&mut self, e: &[f64]) {
+        self.len = fast_expansion_sum_zeroelim(&self.now[..self.len], e, self.other);
+        mem::swap(&mut self.now, &mut self.other);
+    }
+
+    fn most_significant(&self) -> f64 {
+        self.now[self.len - 1]
+    }
+}
+
+/// Returns a positive value if `pa`, `pb` and `pc` occur in counter-clockwise order, a negative
+/// value if they occur in clockwise order, and zero if they are collinear. The result is an
+/// approximation of twice the signed area of the triangle, with the correct sign.
+pub fn orient2d(pa: &Point2D, pb: &Point2D, pc: &Point2D) -> f64 {
+    let detleft = (pa.x - pc.x) * (pb.y - pc.y);
+    let detright = (pa.y - pc.y) * (pb.x - pc.x);
+    let det = detleft - detright;
+
+    let detsum = if detleft > 0.0 {
+        if detright <= 0.0 {
+            record!(Orient2d, Filter);
+            return det;
+        }
+        detleft + detright
+    } else if detleft < 0.0 {
+        if detright >= 0.0 {
+            record!(Orient2d, Filter);
+            return det;
+        }
+        -detleft - detright
+    } else {
+        record!(Orient2d, Filter);
+        return det;
+    };
+
+    let errbound = CCWERRBOUND_A * detsum;
+    if det >= errbound || -det >= errbound {
+        record!(Orient2d, Filter);
+        return det;
+    }
+
+    orient2dadapt(pa, pb, pc, detsum)
+}
+
+fn orient2dadapt(pa: &Point2D, pb: &Point2D, pc: &Point2D, detsum: f64) -> f64 {
+    let acx = pa.x - pc.x;
+    let bcx = pb.x - pc.x;
+    let acy = pa.y - pc.y;
+    let bcy = pb.y - pc.y;
+
+    let b = two_two_diff(two_product(acx, bcy), two_product(acy, bcx));
+
+    let mut det = estimate(&b);
+    let errbound = CCWERRBOUND_B * detsum;
+    if det >= errbound || -det >= errbound {
+        record!(Orient2d, Estimate);
+        return det;
+    }
+
+    let acxtail = two_diff_tail(pa.x, pc.x, acx);
+    let bcxtail = two_diff_tail(pb.x, pc.x, bcx);
+    let acytail = two_diff_tail(pa.y, pc.y, acy);
+    let bcytail = two_diff_tail(pb.y, pc.y, bcy);
+
+    if acxtail == 0.0 && acytail == 0.0 && bcxtail == 0.0 && bcytail == 0.0 {
+        record!(Orient2d, Estimate);
+        return det;
+    }
+
+    let errbound = CCWERRBOUND_C * detsum + RESULTERRBOUND * det.abs();
+    det += (acx * bcytail + bcy * acxtail) - (acy * bcxtail + bcx * acytail);
+    if det >= errbound || -det >= errbound {
+        record!(Orient2d, Tails);
+        return det;
+    }
+
+    let (mut c1, mut c2, mut d) = ([0.0; 8], [0.0; 12], [0.0; 16]);
+
+    let u = two_two_diff(two_product(acxtail, bcy), two_product(acytail, bcx));
+    let c1len = sum(&b, &u, &mut c1);
+
+    let u = two_two_diff(two_product(acx, bcytail), two_product(acy, bcxtail));
+    let c2len = sum(&c1[..c1len], &u, &mut c2);
+
+    let u = two_two_diff(two_product(acxtail, bcytail), two_product(acytail, bcxtail));
+    let dlen = sum(&c2[..c2len], &u, &mut d);
+
+    record!(Orient2d, Exact);
+
+    d[dlen - 1]
+}
+
+/// Returns a positive value if `pd` lies below the plane through `pa`, `pb` and `pc`, which
+/// appear in counter-clockwise order when viewed from above the plane, a negative value if it
+/// lies above the plane, and zero if the points are coplanar. The result is an approximation of
+/// six times the signed volume of the tetrahedron, with the correct sign.
+pub fn orient3d(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D) -> f64 {
+    let (det, permanent) = orient3d_filter(pa, pb, pc, pd);
+
+    let errbound = O3DERRBOUND_A * permanent;
+    if det > errbound || -det > errbound {
+        record!(Orient3d, Filter);
+        return det;
+    }
+
+    orient3dadapt(pa, pb, pc, pd, permanent)
+}
+
+/// The determinant in floating-point arithmetic and the permanent its error bound is
+/// proportional to.
+#[inline]
+fn orient3d_filter(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D) -> (f64, f64) {
+    let adx = pa.x - pd.x;
+    let bdx = pb.x - pd.x;
+    let cdx = pc.x - pd.x;
+    let ady = pa.y - pd.y;
+    let bdy = pb.y - pd.y;
+    let cdy = pc.y - pd.y;
+    let adz = pa.z - pd.z;
+    let bdz = pb.z - pd.z;
+    let cdz = pc.z - pd.z;
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+
+    let det = adz * (bdxcdy - cdxbdy)
+            + bdz * (cdxady - adxcdy)
+            + cdz * (adxbdy - bdxady);
+
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+                  + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+                  + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+    (det, permanent)
+}
+
+/// The two products of the tail of one vertex with the coordinates of the other two, as used in
+/// the last stage of `orient3dadapt`.
+fn orient3d_tail_products(xtail: f64, ytail: f64, px: f64, py: f64, qx: f64, qy: f64) -> ([f64; 4], usize, [f64; 4], usize) {
+    let pair = |(x, y): (f64, f64)| [y, x, 0.0, 0.0];
+
+    if xtail == 0.0 {
+        if ytail == 0.0 {
+            ([0.0; 4], 1, [0.0; 4], 1)
+        } else {
+            (pair(two_product(-ytail, px)), 2, pair(two_product(ytail, qx)), 2)
+        }
+    } else if ytail == 0.0 {
+        (pair(two_product(xtail, py)), 2, pair(two_product(-xtail, qy)), 2)
+    } else {
+        (two_two_diff(two_product(xtail, py), two_product(ytail, px)), 4,
+         two_two_diff(two_product(ytail, qx), two_product(xtail, qy)), 4)
+    }
+}
+
+fn orient3dadapt(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, permanent: f64) -> f64 {
+    let adx = pa.x - pd.x;
+    let bdx = pb.x - pd.x;
+    let cdx = pc.x - pd.x;
+    let ady = pa.y - pd.y;
+    let bdy = pb.y - pd.y;
+    let cdy = pc.y - pd.y;
+    let adz = pa.z - pd.z;
+    let bdz = pb.z - pd.z;
+    let cdz = pc.z - pd.z;
+
+    let (mut adet, mut bdet, mut cdet) = ([0.0; 8], [0.0; 8], [0.0; 8]);
+    let mut abdet = [0.0; 16];
+    let (mut fin1, mut fin2) = ([0.0; 192], [0.0; 192]);
+
+    let bc = two_two_diff(two_product(bdx, cdy), two_product(cdx, bdy));
+    let alen = scale(&bc, adz, &mut adet);
+
+    let ca = two_two_diff(two_product(cdx, ady), two_product(adx, cdy));
+    let blen = scale(&ca, bdz, &mut bdet);
+
+    let ab = two_two_diff(two_product(adx, bdy), two_product(bdx, ady));
+    let clen = scale(&ab, cdz, &mut cdet);
+
+    let ablen = sum(&adet[..alen], &bdet[..blen], &mut abdet);
+    let finlength = sum(&abdet[..ablen], &cdet[..clen], &mut fin1);
+
+    let mut det = estimate(&fin1[..finlength]);
+    let errbound = O3DERRBOUND_B * permanent;
+    if det >= errbound || -det >= errbound {
+        record!(Orient3d, Estimate);
+        return det;
+    }
+
+    let adxtail = two_diff_tail(pa.x, pd.x, adx);
+    let bdxtail = two_diff_tail(pb.x, pd.x, bdx);
+    let cdxtail = two_diff_tail(pc.x, pd.x, cdx);
+    let adytail = two_diff_tail(pa.y, pd.y, ady);
+    let bdytail = two_diff_tail(pb.y, pd.y, bdy);
+    let cdytail = two_diff_tail(pc.y, pd.y, cdy);
+    let adztail = two_diff_tail(pa.z, pd.z, adz);
+    let bdztail = two_diff_tail(pb.z, pd.z, bdz);
+    let cdztail = two_diff_tail(pc.z, pd.z, cdz);
+
+    if adxtail == 0.0 && bdxtail == 0.0 && cdxtail == 0.0 &&
+       adytail == 0.0 && bdytail == 0.0 && cdytail == 0.0 &&
+       adztail == 0.0 && bdztail == 0.0 && cdztail == 0.0 {
+        record!(Orient3d, Estimate);
+        return det;
+    }
+
+    let errbound = O3DERRBOUND_C * permanent + RESULTERRBOUND * det.abs();
+    det += (adz * ((bdx * cdytail + cdy * bdxtail)
+                   - (bdy * cdxtail + cdx * bdytail))
+            + adztail * (bdx * cdy - bdy * cdx))
+         + (bdz * ((cdx * adytail + ady * cdxtail)
+                   - (cdy * adxtail + adx * cdytail))
+            + bdztail * (cdx * ady - cdy * adx))
+         + (cdz * ((adx * bdytail + bdy * adxtail)
+                   - (ady * bdxtail + bdx * adytail))
+            + cdztail * (adx * bdy - ady * bdx));
+    if det >= errbound || -det >= errbound {
+        record!(Orient3d, Tails);
+        return det;
+    }
+
+    let mut fin = Accumulator { now: &mut fin1, other: &mut fin2, len: finlength };
+
+    let (at_b, at_blen, at_c, at_clen) = orient3d_tail_products(adxtail, adytail, bdx, bdy, cdx, cdy);
+    let (bt_c, bt_clen, bt_a, bt_alen) = orient3d_tail_products(bdxtail, bdytail, cdx, cdy, adx, ady);
+    let (ct_a, ct_alen, ct_b, ct_blen) = orient3d_tail_products(cdxtail, cdytail, adx, ady, bdx, bdy);
+
+    let (mut bct, mut cat, mut abt) = ([0.0; 8], [0.0; 8], [0.0; 8]);
+    let mut w = [0.0; 16];
+    let mut v = [0.0; 12];
+
+    let bctlen = sum(&bt_c[..bt_clen], &ct_b[..ct_blen], &mut bct);
+    let wlength = scale(&bct[..bctlen], adz, &mut w);
+    fin.add(&w[..wlength]);
+
+    let catlen = sum(&ct_a[..ct_alen], &at_c[..at_clen], &mut cat);
+    let wlength = scale(&cat[..catlen], bdz, &mut w);
+    fin.add(&w[..wlength]);
+
+    let abtlen = sum(&at_b[..at_blen], &bt_a[..bt_alen], &mut abt);
+    let wlength = scale(&abt[..abtlen], cdz, &mut w);
+    fin.add(&w[..wlength]);
+
+    if adztail != 0.0 {
+        let vlength = scale(&bc, adztail, &mut v);
+        fin.add(&v[..vlength]);
+    }
+    if bdztail != 0.0 {
+        let vlength = scale(&ca, bdztail, &mut v);
+        fin.add(&v[..vlength]);
+    }
+    if cdztail != 0.0 {
+        let vlength = scale(&ab, cdztail, &mut v);
+        fin.add(&v[..vlength]);
+    }
+
+    // Products of two tails, scaled by a z difference and its tail
+    let mut tail_product = |xtail: f64, ytail: f64, z: f64, ztail: f64| {
+        if ytail != 0.0 {
+            let product = two_product(xtail, ytail);
+            fin.add(&two_one_product(product, z));
+            if ztail != 0.0 {
+                fin.add(&two_one_product(product, ztail));
+            }
+        }
+    };
+
+    if adxtail != 0.0 {
+        tail_product(adxtail, bdytail, cdz, cdztail);
+        tail_product(-adxtail, cdytail, bdz, bdztail);
+    }
+    if bdxtail != 0.0 {
+        tail_product(bdxtail, cdytail, adz, adztail);
+        tail_product(-bdxtail, adytail, cdz, cdztail);
+    }
+    if cdxtail != 0.0 {
+        tail_product(cdxtail, adytail, bdz, bdztail);
+        tail_product(-cdxtail, bdytail, adz, adztail);
+    }
+
+    if adztail != 0.0 {
+        let wlength = scale(&bct[..bctlen], adztail, &mut w);
+        fin.add(&w[..wlength]);
+    }
+    if bdztail != 0.0 {
+        let wlength = scale(&cat[..catlen], bdztail, &mut w);
+        fin.add(&w[..wlength]);
+    }
+    if cdztail != 0.0 {
+        let wlength = scale(&abt[..abtlen], cdztail, &mut w);
+        fin.add(&w[..wlength]);
+    }
+
+    record!(Orient3d, Exact);
+
+    fin.most_significant()
+}
+
+/// Returns a positive value if `pd` lies inside the circle through `pa`, `pb` and `pc`, a
+/// negative value if it lies outside, and zero if the four points are cocircular. The points
+/// `pa`, `pb` and `pc` must be in counter-clockwise order, or the sign of the result is
+/// reversed.
+pub fn incircle(pa: &Point2D, pb: &Point2D, pc: &Point2D, pd: &Point2D) -> f64 {
+    let (det, permanent) = incircle_filter(pa, pb, pc, pd);
+
+    let errbound = ICCERRBOUND_A * permanent;
+    if det > errbound || -det > errbound {
+        record!(InCircle, Filter);
+        return det;
+    }
+
+    incircleadapt(pa, pb, pc, pd, permanent)
+}
+
+#[inline]
+fn incircle_filter(pa: &Point2D, pb: &Point2D, pc: &Point2D, pd: &Point2D) -> (f64, f64) {
+    let adx = pa.x - pd.x;
+    let bdx = pb.x - pd.x;
+    let cdx = pc.x - pd.x;
+    let ady = pa.y - pd.y;
+    let bdy = pb.y - pd.y;
+    let cdy = pc.y - pd.y;
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+    let alift = adx * adx + ady * ady;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+    let blift = bdx * bdx + bdy * bdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bdxcdy - cdxbdy)
+            + blift * (cdxady - adxcdy)
+            + clift * (adxbdy - bdxady);
+
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+                  + (cdxady.abs() + adxcdy.abs()) * blift
+                  + (adxbdy.abs() + bdxady.abs()) * clift;
+    (det, permanent)
+}
+
+/// The lifted term `(x^2 + y^2) * pair` of the incircle determinant.
+fn incircle_lift(pair: &[f64; 4], x: f64, y: f64, det: &mut [f64; 32]) -> usize {
+    let (mut xpair, mut xxpair, mut ypair, mut yypair) = ([0.0; 8], [0.0; 16], [0.0; 8], [0.0; 16]);
+
+    let xlen = scale(pair, x, &mut xpair);
+    let xxlen = scale(&xpair[..xlen], x, &mut xxpair);
+    let ylen = scale(pair, y, &mut ypair);
+    let yylen = scale(&ypair[..ylen], y, &mut yypair);
+
+    sum(&xxpair[..xxlen], &yypair[..yylen], det)
+}
+
+/// Adds the terms of one coordinate tail in the last stage of `incircleadapt`, and returns the
+/// tail times the cross product `pair`, which is needed again later.
+fn incircle_tail(fin: &mut Accumulator, pair: &[f64; 4], tail: f64, twice: f64,
+                 (first, s1): (&[f64; 4], f64), (second, s2): (&[f64; 4], f64)) -> ([f64; 8], usize) {
+    let (mut tpair, mut temp8) = ([0.0; 8], [0.0; 8]);
+    let (mut temp16a, mut temp16b, mut temp16c) = ([0.0; 16], [0.0; 16], [0.0; 16]);
+    let (mut temp32a, mut temp48) = ([0.0; 32], [0.0; 48]);
+
+    let tpairlen = scale(pair, tail, &mut tpair);
+    let temp16alen = scale(&tpair[..tpairlen], twice, &mut temp16a);
+
+    let temp8len = scale(first, tail, &mut temp8);
+    let temp16blen = scale(&temp8[..temp8len], s1, &mut temp16b);
+
+    let temp8len = scale(second, tail, &mut temp8);
+    let temp16clen = scale(&temp8[..temp8len], s2, &mut temp16c);
+
+    let temp32alen = sum(&temp16a[..temp16alen], &temp16b[..temp16blen], &mut temp32a);
+    let temp48len = sum(&temp16c[..temp16clen], &temp32a[..temp32alen], &mut temp48);
+    fin.add(&temp48[..temp48len]);
+
+    (tpair, tpairlen)
+}
+
+/// The sums and differences of tail products of the two other vertices, as used in the last
+/// stage of `incircleadapt`.
+fn incircle_tail_pairs((pxtail, pytail): (f64, f64), (px, py): (f64, f64),
+                       (qxtail, qytail): (f64, f64), (qx, qy): (f64, f64)) -> ([f64; 8], usize, [f64; 4], usize) {
+    let u = two_two_sum(two_product(pxtail, qy), two_product(px, qytail));
+    let v = two_two_sum(two_product(qxtail, -py), two_product(qx, -pytail));
+
+    let mut pqt = [0.0; 8];
+    let pqtlen = sum(&u, &v, &mut pqt);
+
+    let pqtt = two_two_diff(two_product(pxtail, qytail), two_product(qxtail, pytail));
+
+    (pqt, pqtlen, pqtt, 4)
+}
+
+/// Adds the terms containing the tail of one coordinate twice or thrice in the last stage of
+/// `incircleadapt`. The terms with a tail of another vertex, `cross`, go between the two
+/// groups.
+fn incircle_tail_products<F: FnMut(&mut Accumulator)>(fin: &mut Accumulator, tpair: &[f64], tail: f64, twice: f64,
+                                                       pqt: &[f64], pqtt: &[f64], mut cross: F) {
+    let (mut tpqt, mut tpqtt) = ([0.0; 16], [0.0; 8]);
+    let (mut temp16a, mut temp16b) = ([0.0; 16], [0.0; 16]);
+    let (mut temp32a, mut temp32b, mut temp48, mut temp64) = ([0.0; 32], [0.0; 32], [0.0; 48], [0.0; 64]);
+
+    let temp16alen = scale(tpair, tail, &mut temp16a);
+    let tpqtlen = scale(pqt, tail, &mut tpqt);
+    let temp32alen = scale(&tpqt[..tpqtlen], twice, &mut temp32a);
+    let temp48len = sum(&temp16a[..temp16alen], &temp32a[..temp32alen], &mut temp48);
+    fin.add(&temp48[..temp48len]);
+
+    cross(fin);
+
+    let temp32alen = scale(&tpqt[..tpqtlen], tail, &mut temp32a);
+    let tpqttlen = scale(pqtt, tail, &mut tpqtt);
+    let temp16alen = scale(&tpqtt[..tpqttlen], twice, &mut temp16a);
+    let temp16blen = scale(&tpqtt[..tpqttlen], tail, &mut temp16b);
+    let temp32blen = sum(&temp16a[..temp16alen], &temp16b[..temp16blen], &mut temp32b);
+    let temp64len = sum(&temp32a[..temp32alen], &temp32b[..temp32blen], &mut temp64);
+    fin.add(&temp64[..temp64len]);
+}
+
+/// Adds `lift * xtail * ytail` to the sum, if `ytail` is not zero.
+fn incircle_cross(fin: &mut Accumulator, lift: &[f64; 4], xtail: f64, ytail: f64) {
+    if ytail != 0.0 {
+        let (mut temp8, mut temp16a) = ([0.0; 8], [0.0; 16]);
+        let temp8len = scale(lift, xtail, &mut temp8);
+        let temp16alen = scale(&temp8[..temp8len], ytail, &mut temp16a);
+        fin.add(&temp16a[..temp16alen]);
+    }
+}
+
+fn incircleadapt(pa: &Point2D, pb: &Point2D, pc: &Point2D, pd: &Point2D, permanent: f64) -> f64 {
+    let adx = pa.x - pd.x;
+    let bdx = pb.x - pd.x;
+    let cdx = pc.x - pd.x;
+    let ady = pa.y - pd.y;
+    let bdy = pb.y - pd.y;
+    let cdy = pc.y - pd.y;
+
+    let (mut adet, mut bdet, mut cdet) = ([0.0; 32], [0.0; 32], [0.0; 32]);
+    let mut abdet = [0.0; 64];
+    let (mut fin1, mut fin2) = ([0.0; 1152], [0.0; 1152]);
+
+    let bc = two_two_diff(two_product(bdx, cdy), two_product(cdx, bdy));
+    let alen = incircle_lift(&bc, adx, ady, &mut adet);
+
+    let ca = two_two_diff(two_product(cdx, ady), two_product(adx, cdy));
+    let blen = incircle_lift(&ca, bdx, bdy, &mut bdet);
+
+    let ab = two_two_diff(two_product(adx, bdy), two_product(bdx, ady));
+    let clen = incircle_lift(&ab, cdx, cdy, &mut cdet);
+
+    let ablen = sum(&adet[..alen], &bdet[..blen], &mut abdet);
+    let finlength = sum(&abdet[..ablen], &cdet[..clen], &mut fin1);
+
+    let mut det = estimate(&fin1[..finlength]);
+    let errbound = ICCERRBOUND_B * permanent;
+    if det >= errbound || -det >= errbound {
+        record!(InCircle, Estimate);
+        return det;
+    }
+
+    let adxtail = two_diff_tail(pa.x, pd.x, adx);
+    let adytail = two_diff_tail(pa.y, pd.y, ady);
+    let bdxtail = two_diff_tail(pb.x, pd.x, bdx);
+    let bdytail = two_diff_tail(pb.y, pd.y, bdy);
+    let cdxtail = two_diff_tail(pc.x, pd.x, cdx);
+    let cdytail = two_diff_tail(pc.y, pd.y, cdy);
+    if adxtail == 0.0 && bdxtail == 0.0 && cdxtail == 0.0 &&
+       adytail == 0.0 && bdytail == 0.0 && cdytail == 0.0 {
+        record!(InCircle, Estimate);
+        return det;
+    }
+
+    let errbound = ICCERRBOUND_C * permanent + RESULTERRBOUND * det.abs();
+    det += ((adx * adx + ady * ady) * ((bdx * cdytail + cdy * bdxtail)
+                                       - (bdy * cdxtail + cdx * bdytail))
+            + 2.0 * (adx * adxtail + ady * adytail) * (bdx * cdy - bdy * cdx))
+         + ((bdx * bdx + bdy * bdy) * ((cdx * adytail + ady * cdxtail)
+                                       - (cdy * adxtail + adx * cdytail))
+            + 2.0 * (bdx * bdxtail + bdy * bdytail) * (cdx * ady - cdy * adx))
+         + ((cdx * cdx + cdy * cdy) * ((adx * bdytail + bdy * adxtail)
+                                       - (ady * bdxtail + bdx * adytail))
+            + 2.0 * (cdx * cdxtail + cdy * cdytail) * (adx * bdy - ady * bdx));
+    if det >= errbound || -det >= errbound {
+        record!(InCircle, Tails);
+        return det;
+    }
+
+    let mut fin = Accumulator { now: &mut fin1, other: &mut fin2, len: finlength };
+
+    let (a_tail, b_tail, c_tail) = (adxtail != 0.0 || adytail != 0.0,
+                                    bdxtail != 0.0 || bdytail != 0.0,
+                                    cdxtail != 0.0 || cdytail != 0.0);
+
+    let lift = |x: f64, y: f64| two_two_sum(square(x), square(y));
+    let (mut aa, mut bb, mut cc) = ([0.0; 4], [0.0; 4], [0.0; 4]);
+    if b_tail || c_tail {
+        aa = lift(adx, ady);
+    }
+    if c_tail || a_tail {
+        bb = lift(bdx, bdy);
+    }
+    if a_tail || b_tail {
+        cc = lift(cdx, cdy);
+    }
+
+    let none = ([0.0; 8], 0);
+    let axtbc = if adxtail != 0.0 { incircle_tail(&mut fin, &bc, adxtail, 2.0 * adx, (&cc, bdy), (&bb, -cdy)) } else { none };
+    let aytbc = if adytail != 0.0 { incircle_tail(&mut fin, &bc, adytail, 2.0 * ady, (&bb, cdx), (&cc, -bdx)) } else { none };
+    let bxtca = if bdxtail != 0.0 { incircle_tail(&mut fin, &ca, bdxtail, 2.0 * bdx, (&aa, cdy), (&cc, -ady)) } else { none };
+    let bytca = if bdytail != 0.0 { incircle_tail(&mut fin, &ca, bdytail, 2.0 * bdy, (&cc, adx), (&aa, -cdx)) } else { none };
+    let cxtab = if cdxtail != 0.0 { incircle_tail(&mut fin, &ab, cdxtail, 2.0 * cdx, (&bb, ady), (&aa, -bdy)) } else { none };
+    let cytab = if cdytail != 0.0 { incircle_tail(&mut fin, &ab, cdytail, 2.0 * cdy, (&aa, bdx), (&bb, -adx)) } else { none };
+
+    let zero = ([0.0; 8], 1, [0.0; 4], 1);
+
+    if a_tail {
+        let (bct, bctlen, bctt, bcttlen) = if b_tail || c_tail {
+            incircle_tail_pairs((bdxtail, bdytail), (bdx, bdy), (cdxtail, cdytail), (cdx, cdy))
+        } else {
+            zero
+        };
+
+        if adxtail != 0.0 {
+            incircle_tail_products(&mut fin, &axtbc.0[..axtbc.1], adxtail, 2.0 * adx, &bct[..bctlen], &bctt[..bcttlen], |fin| {
+                incircle_cross(fin, &cc, adxtail, bdytail);
+                incircle_cross(fin, &bb, -adxtail, cdytail);
+            });
+        }
+        if adytail != 0.0 {
+            incircle_tail_products(&mut fin, &aytbc.0[..aytbc.1], adytail, 2.0 * ady, &bct[..bctlen], &bctt[..bcttlen], |_| {});
+        }
+    }
+    if b_tail {
+        let (cat, catlen, catt, cattlen) = if c_tail || a_tail {
+            incircle_tail_pairs((cdxtail, cdytail), (cdx, cdy), (adxtail, adytail), (adx, ady))
+        } else {
+            zero
+        };
+
+        if bdxtail != 0.0 {
+            incircle_tail_products(&mut fin, &bxtca.0[..bxtca.1], bdxtail, 2.0 * bdx, &cat[..catlen], &catt[..cattlen], |fin| {
+                incircle_cross(fin, &aa, bdxtail, cdytail);
+                incircle_cross(fin, &cc, -bdxtail, adytail);
+            });
+        }
+        if bdytail != 0.0 {
+            incircle_tail_products(&mut fin, &bytca.0[..bytca.1], bdytail, 2.0 * bdy, &cat[..catlen], &catt[..cattlen], |_| {});
+        }
+    }
+    if c_tail {
+        let (abt, abtlen, abtt, abttlen) = if a_tail || b_tail {
+            incircle_tail_pairs((adxtail, adytail), (adx, ady), (bdxtail, bdytail), (bdx, bdy))
+        } else {
+            zero
+        };
+
+        if cdxtail != 0.0 {
+            incircle_tail_products(&mut fin, &cxtab.0[..cxtab.1], cdxtail, 2.0 * cdx, &abt[..abtlen], &abtt[..abttlen], |fin| {
+                incircle_cross(fin, &bb, cdxtail, adytail);
+                incircle_cross(fin, &aa, -cdxtail, bdytail);
+            });
+        }
+        if cdytail != 0.0 {
+            incircle_tail_products(&mut fin, &cytab.0[..cytab.1], cdytail, 2.0 * cdy, &abt[..abtlen], &abtt[..abttlen], |_| {});
+        }
+    }
+
+    record!(InCircle, Exact);
+
+    fin.most_significant()
+}
+
+/// Returns a positive value if `pe` lies inside the sphere through `pa`, `pb`, `pc` and `pd`, a
+/// negative value if it lies outside, and zero if the five points are cospherical. The points
+/// `pa`, `pb`, `pc` and `pd` must be ordered so that they have a positive orientation as defined
+/// by `orient3d`, or the sign of the result is reversed.
+pub fn insphere(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pe: &Point3D) -> f64 {
+    let (det, permanent) = insphere_filter(pa, pb, pc, pd, pe);
+
+    let errbound = ISPERRBOUND_A * permanent;
+    if det > errbound || -det > errbound {
+        record!(InSphere, Filter);
+        return det;
+    }
+
+    insphereadapt(pa, pb, pc, pd, pe, permanent)
+}
+
+#[inline]
+fn insphere_filter(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pe: &Point3D) -> (f64, f64) {
+    let aex = pa.x - pe.x;
+    let bex = pb.x - pe.x;
+    let cex = pc.x - pe.x;
+    let dex = pd.x - pe.x;
+    let aey = pa.y - pe.y;
+    let bey = pb.y - pe.y;
+    let cey = pc.y - pe.y;
+    let dey = pd.y - pe.y;
+    let aez = pa.z - pe.z;
+    let bez = pb.z - pe.z;
+    let cez = pc.z - pe.z;
+    let dez = pd.z - pe.z;
+
+    let aexbey = aex * bey;
+    let bexaey = bex * aey;
+    let ab = aexbey - bexaey;
+    let bexcey = bex * cey;
+    let cexbey = cex * bey;
+    let bc = bexcey - cexbey;
+    let cexdey = cex * dey;
+    let dexcey = dex * cey;
+    let cd = cexdey - dexcey;
+    let dexaey = dex * aey;
+    let aexdey = aex * dey;
+    let da = dexaey - aexdey;
+
+    let aexcey = aex * cey;
+    let cexaey = cex * aey;
+    let ac = aexcey - cexaey;
+    let bexdey = bex * dey;
+    let dexbey = dex * bey;
+    let bd = bexdey - dexbey;
+
+    let abc = aez * bc - bez * ac + cez * ab;
+    let bcd = bez * cd - cez * bd + dez * bc;
+    let cda = cez * da + dez * ac + aez * cd;
+    let dab = dez * ab + aez * bd + bez * da;
+
+    let alift = aex * aex + aey * aey + aez * aez;
+    let blift = bex * bex + bey * bey + bez * bez;
+    let clift = cex * cex + cey * cey + cez * cez;
+    let dlift = dex * dex + dey * dey + dez * dez;
+
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let aezplus = aez.abs();
+    let bezplus = bez.abs();
+    let cezplus = cez.abs();
+    let dezplus = dez.abs();
+    let aexbeyplus = aexbey.abs();
+    let bexaeyplus = bexaey.abs();
+    let bexceyplus = bexcey.abs();
+    let cexbeyplus = cexbey.abs();
+    let cexdeyplus = cexdey.abs();
+    let dexceyplus = dexcey.abs();
+    let dexaeyplus = dexaey.abs();
+    let aexdeyplus = aexdey.abs();
+    let aexceyplus = aexcey.abs();
+    let cexaeyplus = cexaey.abs();
+    let bexdeyplus = bexdey.abs();
+    let dexbeyplus = dexbey.abs();
+    let permanent = ((cexdeyplus + dexceyplus) * bezplus
+                     + (dexbeyplus + bexdeyplus) * cezplus
+                     + (bexceyplus + cexbeyplus) * dezplus)
+                  * alift
+                  + ((dexaeyplus + aexdeyplus) * cezplus
+                     + (aexceyplus + cexaeyplus) * dezplus
+                     + (cexdeyplus + dexceyplus) * aezplus)
+                  * blift
+                  + ((aexbeyplus + bexaeyplus) * dezplus
+                     + (bexdeyplus + dexbeyplus) * aezplus
+                     + (dexaeyplus + aexdeyplus) * bezplus)
+                  * clift
+                  + ((bexceyplus + cexbeyplus) * aezplus
+                     + (cexaeyplus + aexceyplus) * bezplus
+                     + (aexbeyplus + bexaeyplus) * cezplus)
+                  * dlift;
+    (det, permanent)
+}
+
+/// The lifted term `sign * (x^2 + y^2 + z^2) * (first * s1 + second * s2 + third * s3)` of the
+/// insphere determinant, with the third product added first.
+fn insphere_lift(first: (&[f64; 4], f64), second: (&[f64; 4], f64), third: (&[f64; 4], f64),
+                 (x, y, z): (f64, f64, f64), sign: f64, det: &mut [f64; 288]) -> usize {
+    let (mut temp8a, mut temp8b, mut temp8c) = ([0.0; 8], [0.0; 8], [0.0; 8]);
+    let (mut temp16, mut temp24, mut temp48) = ([0.0; 16], [0.0; 24], [0.0; 48]);
+    let (mut xdet, mut ydet, mut zdet, mut xydet) = ([0.0; 96], [0.0; 96], [0.0; 96], [0.0; 192]);
+
+    let temp8alen = scale(first.0, first.1, &mut temp8a);
+    let temp8blen = scale(second.0, second.1, &mut temp8b);
+    let temp8clen = scale(third.0, third.1, &mut temp8c);
+    let temp16len = sum(&temp8a[..temp8alen], &temp8b[..temp8blen], &mut temp16);
+    let temp24len = sum(&temp8c[..temp8clen], &temp16[..temp16len], &mut temp24);
+
+    let temp48len = scale(&temp24[..temp24len], x, &mut temp48);
+    let xlen = scale(&temp48[..temp48len], sign * x, &mut xdet);
+    let temp48len = scale(&temp24[..temp24len], y, &mut temp48);
+    let ylen = scale(&temp48[..temp48len], sign * y, &mut ydet);
+    let temp48len = scale(&temp24[..temp24len], z, &mut temp48);
+    let zlen = scale(&temp48[..temp48len], sign * z, &mut zdet);
+
+    let xylen = sum(&xdet[..xlen], &ydet[..ylen], &mut xydet);
+    sum(&xydet[..xylen], &zdet[..zlen], det)
+}
+
+fn insphereadapt(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pe: &Point3D, permanent: f64) -> f64 {
+    let aex = pa.x - pe.x;
+    let bex = pb.x - pe.x;
+    let cex = pc.x - pe.x;
+    let dex = pd.x - pe.x;
+    let aey = pa.y - pe.y;
+    let bey = pb.y - pe.y;
+    let cey = pc.y - pe.y;
+    let dey = pd.y - pe.y;
+    let aez = pa.z - pe.z;
+    let bez = pb.z - pe.z;
+    let cez = pc.z - pe.z;
+    let dez = pd.z - pe.z;
+
+    let ab = two_two_diff(two_product(aex, bey), two_product(bex, aey));
+    let bc = two_two_diff(two_product(bex, cey), two_product(cex, bey));
+    let cd = two_two_diff(two_product(cex, dey), two_product(dex, cey));
+    let da = two_two_diff(two_product(dex, aey), two_product(aex, dey));
+    let ac = two_two_diff(two_product(aex, cey), two_product(cex, aey));
+    let bd = two_two_diff(two_product(bex, dey), two_product(dex, bey));
+
+    let (mut adet, mut bdet, mut cdet, mut ddet) = ([0.0; 288], [0.0; 288], [0.0; 288], [0.0; 288]);
+    let (mut abdet, mut cddet) = ([0.0; 576], [0.0; 576]);
+    let mut fin1 = [0.0; 1152];
+
+    // The negation of the factor is folded into the second scaling for `a` and `c`
+    let alen = insphere_lift((&cd, bez), (&bd, -cez), (&bc, dez), (aex, aey, aez), -1.0, &mut adet);
+    let blen = insphere_lift((&da, cez), (&ac, dez), (&cd, aez), (bex, bey, bez), 1.0, &mut bdet);
+    let clen = insphere_lift((&ab, dez), (&bd, aez), (&da, bez), (cex, cey, cez), -1.0, &mut cdet);
+    let dlen = insphere_lift((&bc, aez), (&ac, -bez), (&ab, cez), (dex, dey, dez), 1.0, &mut ddet);
+
+    let ablen = sum(&adet[..alen], &bdet[..blen], &mut abdet);
+    let cdlen = sum(&cdet[..clen], &ddet[..dlen], &mut cddet);
+    let finlength = sum(&abdet[..ablen], &cddet[..cdlen], &mut fin1);
+
+    let mut det = estimate(&fin1[..finlength]);
+    let errbound = ISPERRBOUND_B * permanent;
+    if det >= errbound || -det >= errbound {
+        record!(InSphere, Estimate);
+        return det;
+    }
+
+    let aextail = two_diff_tail(pa.x, pe.x, aex);
+    let aeytail = two_diff_tail(pa.y, pe.y, aey);
+    let aeztail = two_diff_tail(pa.z, pe.z, aez);
+    let bextail = two_diff_tail(pb.x, pe.x, bex);
+    let beytail = two_diff_tail(pb.y, pe.y, bey);
+    let beztail = two_diff_tail(pb.z, pe.z, bez);
+    let cextail = two_diff_tail(pc.x, pe.x, cex);
+    let ceytail = two_diff_tail(pc.y, pe.y, cey);
+    let ceztail = two_diff_tail(pc.z, pe.z, cez);
+    let dextail = two_diff_tail(pd.x, pe.x, dex);
+    let deytail = two_diff_tail(pd.y, pe.y, dey);
+    let deztail = two_diff_tail(pd.z, pe.z, dez);
+    if aextail == 0.0 && aeytail == 0.0 && aeztail == 0.0 &&
+       bextail == 0.0 && beytail == 0.0 && beztail == 0.0 &&
+       cextail == 0.0 && ceytail == 0.0 && ceztail == 0.0 &&
+       dextail == 0.0 && deytail == 0.0 && deztail == 0.0 {
+        record!(InSphere, Estimate);
+        return det;
+    }
+
+    let (ab3, bc3, cd3, da3, ac3, bd3) = (ab[3], bc[3], cd[3], da[3], ac[3], bd[3]);
+
+    let errbound = ISPERRBOUND_C * permanent + RESULTERRBOUND * det.abs();
+    let abeps = (aex * beytail + bey * aextail)
+              - (aey * bextail + bex * aeytail);
+    let bceps = (bex * ceytail + cey * bextail)
+              - (bey * cextail + cex * beytail);
+    let cdeps = (cex * deytail + dey * cextail)
+              - (cey * dextail + dex * ceytail);
+    let daeps = (dex * aeytail + aey * dextail)
+              - (dey * aextail + aex * deytail);
+    let aceps = (aex * ceytail + cey * aextail)
+              - (aey * cextail + cex * aeytail);
+    let bdeps = (bex * deytail + dey * bextail)
+              - (bey * dextail + dex * beytail);
+    det += (((bex * bex + bey * bey + bez * bez)
+             * ((cez * daeps + dez * aceps + aez * cdeps)
+                + (ceztail * da3 + deztail * ac3 + aeztail * cd3))
+             + (dex * dex + dey * dey + dez * dez)
+             * ((aez * bceps - bez * aceps + cez * abeps)
+                + (aeztail * bc3 - beztail * ac3 + ceztail * ab3)))
+            - ((aex * aex + aey * aey + aez * aez)
+             * ((bez * cdeps - cez * bdeps + dez * bceps)
+                + (beztail * cd3 - ceztail * bd3 + deztail * bc3))
+             + (cex * cex + cey * cey + cez * cez)
+             * ((dez * abeps + aez * bdeps + bez * daeps)
+                + (deztail * ab3 + aeztail * bd3 + beztail * da3))))
+         + 2.0 * (((bex * bextail + bey * beytail + bez * beztail)
+                   * (cez * da3 + dez * ac3 + aez * cd3)
+                   + (dex * dextail + dey * deytail + dez * deztail)
+                   * (aez * bc3 - bez * ac3 + cez * ab3))
+                  - ((aex * aextail + aey * aeytail + aez * aeztail)
+                   * (bez * cd3 - cez * bd3 + dez * bc3)
+                   + (cex * cextail + cey * ceytail + cez * ceztail)
+                   * (dez * ab3 + aez * bd3 + bez * da3)));
+    if det >= errbound || -det >= errbound {
+        record!(InSphere, Tails);
+        return det;
+    }
+
+    record!(InSphere, Exact);
+
+    insphereexact(pa, pb, pc, pd, pe)
+}
+
+/// The exact 2x2 determinant `p.x * q.y - q.x * p.y`.
+fn cross_exact(p: &Point3D, q: &Point3D) -> [f64; 4] {
+    two_two_diff(two_product(p.x, q.y), two_product(q.x, p.y))
+}
+
+/// The exact 3x3 determinant `first * s1 + second * s2 + third * s3`, with the third product
+/// added last.
+fn insphere_triple(first: (&[f64; 4], f64), second: (&[f64; 4], f64), third: (&[f64; 4], f64), det: &mut [f64; 24]) -> usize {
+    let (mut temp8a, mut temp8b, mut temp16) = ([0.0; 8], [0.0; 8], [0.0; 16]);
+
+    let temp8alen = scale(first.0, first.1, &mut temp8a);
+    let temp8blen = scale(second.0, second.1, &mut temp8b);
+    let temp16len = sum(&temp8a[..temp8alen], &temp8b[..temp8blen], &mut temp16);
+    let temp8alen = scale(third.0, third.1, &mut temp8a);
+
+    sum(&temp8a[..temp8alen], &temp16[..temp16len], det)
+}
+
+/// The lifted term `(x^2 + y^2 + z^2) * ((p1 + p2) - (n1 + n2))` of the exact insphere
+/// determinant.
+fn insphere_exact_lift(p1: &[f64], p2: &[f64], n1: &[f64], n2: &[f64], p: &Point3D, det: &mut [f64; 1152]) -> usize {
+    let (mut temp48a, mut temp48b) = ([0.0; 48], [0.0; 48]);
+    let mut quad = [0.0; 96];
+    let mut temp192 = [0.0; 192];
+    let (mut det384x, mut det384y, mut det384z) = ([0.0; 384], [0.0; 384], [0.0; 384]);
+    let mut detxy = [0.0; 768];
+
+    let temp48alen = sum(p1, p2, &mut temp48a);
+    let temp48blen = sum(n1, n2, &mut temp48b);
+    for c in &mut temp48b[..temp48blen] {
+        *c = -*c;
+    }
+    let quadlen = sum(&temp48a[..temp48alen], &temp48b[..temp48blen], &mut quad);
+
+    let xlen = scale(&quad[..quadlen], p.x, &mut temp192);
+    let xlen = scale(&temp192[..xlen], p.x, &mut det384x);
+    let ylen = scale(&quad[..quadlen], p.y, &mut temp192);
+    let ylen = scale(&temp192[..ylen], p.y, &mut det384y);
+    let zlen = scale(&quad[..quadlen], p.z, &mut temp192);
+    let zlen = scale(&temp192[..zlen], p.z, &mut det384z);
+
+    let xylen = sum(&det384x[..xlen], &det384y[..ylen], &mut detxy);
+    sum(&detxy[..xylen], &det384z[..zlen], det)
+}
+
+fn insphereexact(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pe: &Point3D) -> f64 {
+    let ab = cross_exact(pa, pb);
+    let bc = cross_exact(pb, pc);
+    let cd = cross_exact(pc, pd);
+    let de = cross_exact(pd, pe);
+    let ea = cross_exact(pe, pa);
+    let ac = cross_exact(pa, pc);
+    let bd = cross_exact(pb, pd);
+    let ce = cross_exact(pc, pe);
+    let da = cross_exact(pd, pa);
+    let eb = cross_exact(pe, pb);
+
+    let (mut abc, mut bcd, mut cde, mut dea, mut eab) = ([0.0; 24], [0.0; 24], [0.0; 24], [0.0; 24], [0.0; 24]);
+    let (mut abd, mut bce, mut cda, mut deb, mut eac) = ([0.0; 24], [0.0; 24], [0.0; 24], [0.0; 24], [0.0; 24]);
+
+    let abclen = insphere_triple((&bc, pa.z), (&ac, -pb.z), (&ab, pc.z), &mut abc);
+    let bcdlen = insphere_triple((&cd, pb.z), (&bd, -pc.z), (&bc, pd.z), &mut bcd);
+    let cdelen = insphere_triple((&de, pc.z), (&ce, -pd.z), (&cd, pe.z), &mut cde);
+    let dealen = insphere_triple((&ea, pd.z), (&da, -pe.z), (&de, pa.z), &mut dea);
+    let eablen = insphere_triple((&ab, pe.z), (&eb, -pa.z), (&ea, pb.z), &mut eab);
+    let abdlen = insphere_triple((&bd, pa.z), (&da, pb.z), (&ab, pd.z), &mut abd);
+    let bcelen = insphere_triple((&ce, pb.z), (&eb, pc.z), (&bc, pe.z), &mut bce);
+    let cdalen = insphere_triple((&da, pc.z), (&ac, pd.z), (&cd, pa.z), &mut cda);
+    let deblen = insphere_triple((&eb, pd.z), (&bd, pe.z), (&de, pb.z), &mut deb);
+    let eaclen = insphere_triple((&ac, pe.z), (&ce, pa.z), (&ea, pc.z), &mut eac);
+
+    let (abc, bcd, cde, dea, eab) = (&abc[..abclen], &bcd[..bcdlen], &cde[..cdelen], &dea[..dealen], &eab[..eablen]);
+    let (abd, bce, cda, deb, eac) = (&abd[..abdlen], &bce[..bcelen], &cda[..cdalen], &deb[..deblen], &eac[..eaclen]);
+
+    let (mut adet, mut bdet, mut cdet, mut ddet, mut edet) = ([0.0; 1152], [0.0; 1152], [0.0; 1152], [0.0; 1152], [0.0; 1152]);
+
+    let alen = insphere_exact_lift(cde, bce, deb, bcd, pa, &mut adet);
+    let blen = insphere_exact_lift(dea, cda, eac, cde, pb, &mut bdet);
+    let clen = insphere_exact_lift(eab, deb, abd, dea, pc, &mut cdet);
+    let dlen = insphere_exact_lift(abc, eac, bce, eab, pd, &mut ddet);
+    let elen = insphere_exact_lift(bcd, abd, cda, abc, pe, &mut edet);
+
+    let (mut abdet, mut cddet, mut cdedet) = ([0.0; 2304], [0.0; 2304], [0.0; 3456]);
+    let mut deter = [0.0; 5760];
+
+    let ablen = sum(&adet[..alen], &bdet[..blen], &mut abdet);
+    let cdlen = sum(&cdet[..clen], &ddet[..dlen], &mut cddet);
+    let cdelen = sum(&cddet[..cdlen], &edet[..elen], &mut cdedet);
+    let deterlen = sum(&abdet[..ablen], &cdedet[..cdelen], &mut deter);
+
+    deter[deterlen - 1]
+}
+
+/// The power test, the weighted version of `incircle`: returns a positive value if the power
+/// distance of `pd` to the circle orthogonal to `pa`, `pb` and `pc` is negative, a negative value
+/// if it is positive, and zero if it vanishes. This is the orientation of the points lifted to
+/// `x^2 + y^2 - weight`. The points `pa`, `pb` and `pc` must be in counter-clockwise order, or
+/// the sign of the result is reversed.
+pub fn power_test_2d(pa: &Weighted<Point2D>, pb: &Weighted<Point2D>, pc: &Weighted<Point2D>, pd: &Weighted<Point2D>) -> f64 {
+    let (det, permanent) = power_test_2d_filter(pa, pb, pc, pd);
+
+    let errbound = PW2ERRBOUND_A * permanent;
+    if det > errbound || -det > errbound {
+        record!(Power2d, Filter);
+        return det;
+    }
+
+    record!(Power2d, Exact);
+    power_test_exact(&[([pa.point.x, pa.point.y], pa.weight), ([pb.point.x, pb.point.y], pb.weight),
+                       ([pc.point.x, pc.point.y], pc.weight), ([pd.point.x, pd.point.y], pd.weight)])
+}
+
+/// The lifted coordinate relative to `origin`, and its permanent.
+#[inline]
+fn power_lift(d: &[f64], weight: f64, origin: f64) -> (f64, f64) {
+    let square = d.iter().map(|&x| x * x).fold(0.0, |sum, x| sum + x);
+    let w = weight - origin;
+    (square - w, square + w.abs())
+}
+
+#[inline]
+fn power_test_2d_filter(pa: &Weighted<Point2D>, pb: &Weighted<Point2D>, pc: &Weighted<Point2D>, pd: &Weighted<Point2D>) -> (f64, f64) {
+    let d = |p: &Weighted<Point2D>| [p.point.x - pd.point.x, p.point.y - pd.point.y];
+    let (a, b, c) = (d(pa), d(pb), d(pc));
+    let (alift, alift_plus) = power_lift(&a, pa.weight, pd.weight);
+    let (blift, blift_plus) = power_lift(&b, pb.weight, pd.weight);
+    let (clift, clift_plus) = power_lift(&c, pc.weight, pd.weight);
+
+    let cross = |u: [f64; 2], v: [f64; 2]| {
+        let (uv, vu) = (u[0] * v[1], v[0] * u[1]);
+        (uv - vu, uv.abs() + vu.abs())
+    };
+    let (bc, bc_plus) = cross(b, c);
+    let (ca, ca_plus) = cross(c, a);
+    let (ab, ab_plus) = cross(a, b);
+
+    let det = alift * bc + blift * ca + clift * ab;
+    let permanent = alift_plus * bc_plus + blift_plus * ca_plus + clift_plus * ab_plus;
+    (det, permanent)
+}
+
+/// The power test in 3D, the weighted version of `insphere`: returns a positive value if the
+/// power distance of `pe` to the sphere orthogonal to `pa`, `pb`, `pc` and `pd` is negative, a
+/// negative value if it is positive, and zero if it vanishes. The points `pa`, `pb`, `pc` and
+/// `pd` must have a positive orientation as defined by `orient3d`, or the sign of the result is
+/// reversed.
+pub fn power_test_3d(pa: &Weighted<Point3D>, pb: &Weighted<Point3D>, pc: &Weighted<Point3D>, pd: &Weighted<Point3D>, pe: &Weighted<Point3D>) -> f64 {
+    let (det, permanent) = power_test_3d_filter(pa, pb, pc, pd, pe);
+
+    let errbound = PW3ERRBOUND_A * permanent;
+    if det > errbound || -det > errbound {
+        record!(Power3d, Filter);
+        return det;
+    }
+
+    let row = |p: &Weighted<Point3D>| ([p.point.x, p.point.y, p.point.z], p.weight);
+    record!(Power3d, Exact);
+    power_test_exact(&[row(pa), row(pb), row(pc), row(pd), row(pe)])
+}
+
+#[inline]
+fn power_test_3d_filter(pa: &Weighted<Point3D>, pb: &Weighted<Point3D>, pc: &Weighted<Point3D>, pd: &Weighted<Point3D>, pe: &Weighted<Point3D>) -> (f64, f64) {
+    let d = |p: &Weighted<Point3D>| [p.point.x - pe.point.x, p.point.y - pe.point.y, p.point.z - pe.point.z];
+    let rows = [d(pa), d(pb), d(pc), d(pd)];
+    let lifts = [power_lift(&rows[0], pa.weight, pe.weight), power_lift(&rows[1], pb.weight, pe.weight),
+                 power_lift(&rows[2], pc.weight, pe.weight), power_lift(&rows[3], pd.weight, pe.weight)];
+
+    // The minor of the rows other than `skip`, and its permanent
+    let minor = |skip: usize| {
+        let mut r = rows.iter().enumerate().filter(|&(i, _)| i != skip).map(|(_, r)| r);
+        let (u, v, w) = (r.next().unwrap(), r.next().unwrap(), r.next().unwrap());
+        let (vw, wv) = (v[0] * w[1], w[0] * v[1]);
+        let (wu, uw) = (w[0] * u[1], u[0] * w[1]);
+        let (uv, vu) = (u[0] * v[1], v[0] * u[1]);
+        let det = u[2] * (vw - wv) + v[2] * (wu - uw) + w[2] * (uv - vu);
+        let permanent = u[2].abs() * (vw.abs() + wv.abs()) + v[2].abs() * (wu.abs() + uw.abs()) + w[2].abs() * (uv.abs() + vu.abs());
+        (det, permanent)
+    };
+    let (bcd, bcd_plus) = minor(0);
+    let (acd, acd_plus) = minor(1);
+    let (abd, abd_plus) = minor(2);
+    let (abc, abc_plus) = minor(3);
+
+    let det = (lifts[3].0 * abc - lifts[2].0 * abd) + (lifts[1].0 * acd - lifts[0].0 * bcd);
+    let permanent = lifts[3].1 * abc_plus + lifts[2].1 * abd_plus + lifts[1].1 * acd_plus + lifts[0].1 * bcd_plus;
+    (det, permanent)
+}
+
+/// The exact determinant of the power tests, with the rows of all but the last point relative to
+/// the last one, and lifted by their power.
+fn power_test_exact<C: AsRef<[f64]>>(points: &[(C, f64)]) -> f64 {
+    let ((origin, weight), rest) = points.split_last().unwrap();
+    let origin = origin.as_ref();
+
+    let matrix = rest.iter().map(|&(ref p, w)| {
+        let mut row = p.as_ref().iter().zip(origin).map(|(&x, &o)| Expansion::difference(x, o)).collect::<Vec<_>>();
+        let lift = row.iter().fold(-Expansion::difference(w, *weight), |sum, d| sum + d * d);
+        row.push(lift);
+        row
+    }).collect::<Vec<_>>();
+
+    determinant(&matrix).approximate()
+}
+
+/// The orientation of `D + 1` points in `D` dimensions: the determinant of the differences of
+/// the first `D` points to the last one, so it has the sign of `orient2d` and `orient3d` in two
+/// and three dimensions.
+pub fn orient_nd<const D: usize>(points: &[[f64; D]]) -> f64 {
+    assert_eq!(points.len(), D + 1);
+    let (origin, rest) = points.split_last().unwrap();
+
+    let matrix = rest.iter().map(|p| p.iter().zip(origin).map(|(&x, &o)| x - o).collect()).collect::<Vec<_>>();
+    let (det, permanent) = determinant_filter(&matrix);
+    if !permanent.is_finite() {
+        // A coordinate is not finite, or the determinant overflows
+        return f64::NAN;
+    }
+
+    let errbound = determinant_errbound(D, 1) * permanent;
+    if det > errbound || -det > errbound {
+        record!(OrientNd, Filter);
+        return det;
+    }
+
+    record!(OrientNd, Exact);
+    let matrix = rest.iter().map(|p| {
+        p.iter().zip(origin).map(|(&x, &o)| Expansion::difference(x, o)).collect()
+    }).collect::<Vec<_>>();
+    determinant(&matrix).approximate()
+}
+
+/// The insphere test in `D` dimensions: returns a positive value if the last of the `D + 2`
+/// points lies inside the sphere through the others, a negative value if it lies outside, and
+/// zero if it lies on the sphere, provided the others have a positive orientation as defined by
+/// `orient_nd`. In two and three dimensions, it has the sign of `incircle` and `insphere`.
+pub fn insphere_nd<const D: usize>(points: &[[f64; D]]) -> f64 {
+    assert_eq!(points.len(), D + 2);
+    let (origin, rest) = points.split_last().unwrap();
+
+    let matrix = rest.iter().map(|p| {
+        let mut row = p.iter().zip(origin).map(|(&x, &o)| x - o).collect::<Vec<_>>();
+        let lift = row.iter().fold(0.0, |sum, &d| sum + d * d);
+        row.push(lift);
+        row
+    }).collect::<Vec<_>>();
+    let (det, permanent) = determinant_filter(&matrix);
+    if !permanent.is_finite() {
+        // A coordinate is not finite, or the determinant overflows
+        return f64::NAN;
+    }
+
+    // The lifted coordinates are rounded after a difference, a square and D - 1 sums
+    let errbound = determinant_errbound(D + 1, D + 2) * permanent;
+    if det > errbound || -det > errbound {
+        record!(InSphereNd, Filter);
+        return det;
+    }
+
+    record!(InSphereNd, Exact);
+    power_test_exact(&points.iter().map(|&p| (p, 0.0)).collect::<Vec<_>>())
+}
+
+/// The determinant of a square matrix by cofactor expansion along the first row, and the
+/// permanent of the absolute values of its entries.
+fn determinant_filter(matrix: &[Vec<f64>]) -> (f64, f64) {
+    if matrix.len() == 1 {
+        return (matrix[0][0], matrix[0][0].abs());
+    }
+
+    let (mut det, mut permanent) = (0.0, 0.0);
+    for (c, &entry) in matrix[0].iter().enumerate() {
+        let minor = matrix[1..].iter().map(|row| {
+            row.iter().enumerate().filter(|&(k, _)| k != c).map(|(_, &e)| e).collect()
+        }).collect::<Vec<_>>();
+        let (minor_det, minor_permanent) = determinant_filter(&minor);
+
+        det = if c % 2 == 0 { det + entry * minor_det } else { det - entry * minor_det };
+        permanent += entry.abs() * minor_permanent;
+    }
+
+    (det, permanent)
+}
+
+/// A bound on the error of `determinant_filter` for an `n` by `n` matrix, relative to the
+/// permanent, if each entry is rounded in at most `rounding` operations. Each term of the
+/// expansion is rounded in at most `n (n + 1) / 2` operations of its own and `n * rounding` of
+/// its entries; the factor two covers the higher order terms and the rounding of the permanent.
+fn determinant_errbound(n: usize, rounding: usize) -> f64 {
+    let operations = n * (n + 1) / 2 + n * rounding;
+    2.0 * operations as f64 * EPSILON
+}
+
+/// Evaluates a predicate for many points: the floating-point filter runs over all of them first,
+/// in a loop without branches that the compiler can vectorize. Only the results it cannot
+/// certify are recomputed by `adapt`, given the permanent from the filter. Returns the number of
+/// those.
+fn batch<P, F, A>(points: &[P], out: &mut [f64], bound: f64, filter: F, adapt: A) -> usize
+    where F: Fn(&P) -> (f64, f64), A: Fn(&P, f64) -> f64 {
+    assert_eq!(points.len(), out.len());
+
+    let mut permanents = vec!(0.0; points.len());
+    for ((p, det), permanent) in points.iter().zip(out.iter_mut()).zip(permanents.iter_mut()) {
+        let (d, q) = filter(p);
+        *det = d;
+        *permanent = q;
+    }
+
+    let mut adapted = 0;
+    for ((p, det), &permanent) in points.iter().zip(out.iter_mut()).zip(&permanents) {
+        let errbound = bound * permanent;
+        if *det > errbound || -*det > errbound {
+            continue;
+        }
+        *det = adapt(p, permanent);
+        adapted += 1;
+    }
+
+    adapted
+}
+
+/// Evaluates `orient2d(pa, pb, pc)` for each point of `pcs`, writing the results to `out`. The
+/// results are identical to those of the single evaluations.
+pub fn orient2d_batch(pa: &Point2D, pb: &Point2D, pcs: &[Point2D], out: &mut [f64]) {
+    let adapted = batch(pcs, out, CCWERRBOUND_A, |pc| {
+        let detleft = (pa.x - pc.x) * (pb.y - pc.y);
+        let detright = (pa.y - pc.y) * (pb.x - pc.x);
+        (detleft - detright, detleft.abs() + detright.abs())
+    }, |pc, _| orient2d(pa, pb, pc));
+    record!(Orient2d, Filter, pcs.len() - adapted);
+}
+
+/// Evaluates `orient3d(pa, pb, pc, pd)` for each point of `pds`, like `orient2d_batch`.
+pub fn orient3d_batch(pa: &Point3D, pb: &Point3D, pc: &Point3D, pds: &[Point3D], out: &mut [f64]) {
+    let adapted = batch(pds, out, O3DERRBOUND_A, |pd| orient3d_filter(pa, pb, pc, pd),
+                        |pd, permanent| orient3dadapt(pa, pb, pc, pd, permanent));
+    record!(Orient3d, Filter, pds.len() - adapted);
+}
+
+/// Evaluates `incircle(pa, pb, pc, pd)` for each point of `pds`, like `orient2d_batch`.
+pub fn incircle_batch(pa: &Point2D, pb: &Point2D, pc: &Point2D, pds: &[Point2D], out: &mut [f64]) {
+    let adapted = batch(pds, out, ICCERRBOUND_A, |pd| incircle_filter(pa, pb, pc, pd),
+                        |pd, permanent| incircleadapt(pa, pb, pc, pd, permanent));
+    record!(InCircle, Filter, pds.len() - adapted);
+}
+
+/// Evaluates `insphere(pa, pb, pc, pd, pe)` for each point of `pes`, like `orient2d_batch`.
+pub fn insphere_batch(pa: &Point3D, pb: &Point3D, pc: &Point3D, pd: &Point3D, pes: &[Point3D], out: &mut [f64]) {
+    let adapted = batch(pes, out, ISPERRBOUND_A, |pe| insphere_filter(pa, pb, pc, pd, pe),
+                        |pe, permanent| insphereadapt(pa, pb, pc, pd, pe, permanent));
+    record!(InSphere, Filter, pes.len() - adapted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::quickcheck;
+
+    fn sign(x: f64) -> i32 {
+        if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 }
+    }
+
+    #[test]
+    fn constants_match_exactinit() {
+        // Shewchuk's `exactinit` halves epsilon until it no longer changes 1.0, and doubles the
+        // splitter every other time
+        let (mut epsilon, mut splitter, mut every_other) = (1.0f64, 1.0f64, true);
+        loop {
+            epsilon *= 0.5;
+            if every_other {
+                splitter *= 2.0;
+            }
+            every_other = !every_other;
+            if 1.0 + epsilon == 1.0 {
+                break;
+            }
+        }
+
+        assert_eq!(EPSILON, epsilon);
+        assert_eq!(SPLITTER, splitter + 1.0);
+        assert_eq!(ICCERRBOUND_A, (10.0 + 96.0 * epsilon) * epsilon);
+    }
+
+    #[test]
+    fn orient2d_near_collinear_points() {
+        // Naive evaluation gets most of these wrong, the exact sign is that of `j - i`
+        let (q, r) = (Point2D::new(12.0, 12.0), Point2D::new(24.0, 24.0));
+        let ulp = 0.5f64.powi(53);
+
+        for i in 0..64i32 {
+            for j in 0..64 {
+                let p = Point2D::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                assert_eq!(sign(orient2d(&p, &q, &r)), (j - i).signum());
+            }
+        }
+    }
+
+    #[test]
+    fn orient3d_near_coplanar_points() {
+        let (a, b, c) = (Point3D::new(12.0, 0.0, 12.0), Point3D::new(24.0, 0.0, 24.0), Point3D::new(0.0, 12.0, 0.0));
+        let ulp = 0.5f64.powi(53);
+
+        for i in 0..64i32 {
+            for j in 0..64 {
+                let d = Point3D::new(0.5 + i as f64 * ulp, 0.5, 0.5 + j as f64 * ulp);
+                assert_eq!(sign(orient3d(&a, &b, &c, &d)), (i - j).signum());
+            }
+        }
+    }
+
+    #[test]
+    fn incircle_near_cocircular_points() {
+        let (a, b, c) = (Point2D::new(1.0, 0.0), Point2D::new(0.0, 1.0), Point2D::new(-1.0, 0.0));
+        let ulp = 0.5f64.powi(53);
+
+        assert_eq!(incircle(&a, &b, &c, &Point2D::new(0.0, -1.0)), 0.0);
+        for k in 1..64 {
+            assert!(incircle(&a, &b, &c, &Point2D::new(0.0, -1.0 + k as f64 * ulp)) > 0.0);
+            assert!(incircle(&a, &b, &c, &Point2D::new(0.0, -1.0 - k as f64 * 2.0 * ulp)) < 0.0);
+            assert!(incircle(&a, &b, &c, &Point2D::new(k as f64 * ulp, -1.0)) < 0.0);
+        }
+    }
+
+    #[test]
+    fn insphere_near_cospherical_points() {
+        let (a, b, c, d) = (Point3D::new(1.0, 0.0, 0.0), Point3D::new(0.0, 1.0, 0.0), Point3D::new(0.0, 0.0, 1.0), Point3D::new(-1.0, 0.0, 0.0));
+        let orientation = sign(orient3d(&a, &b, &c, &d)) as f64;
+        let ulp = 0.5f64.powi(53);
+
+        assert_eq!(insphere(&a, &b, &c, &d, &Point3D::new(0.0, 0.0, -1.0)), 0.0);
+        for k in 1..64 {
+            assert!(orientation * insphere(&a, &b, &c, &d, &Point3D::new(0.0, 0.0, -1.0 + k as f64 * ulp)) > 0.0);
+            assert!(orientation * insphere(&a, &b, &c, &d, &Point3D::new(0.0, 0.0, -1.0 - k as f64 * 2.0 * ulp)) < 0.0);
+            assert!(orientation * insphere(&a, &b, &c, &d, &Point3D::new(k as f64 * ulp, 0.0, -1.0)) < 0.0);
+        }
+    }
+
+    #[test]
+    fn signs_are_invariant_under_cyclic_permutation() {
+        fn signs_are_invariant_under_cyclic_permutation(coordinates: Vec<i8>) -> bool {
+            // Coordinates that are not exactly representable create many near-degenerate cases
+            let p = coordinates.chunks(2).filter(|c| c.len() == 2).map(|c| Point2D::new(c[0] as f64 * 0.1, c[1] as f64 * 0.1)).take(4).collect::<Vec<_>>();
+            if p.len() < 4 {
+                return true;
+            }
+
+            sign(orient2d(&p[0], &p[1], &p[2])) == sign(orient2d(&p[1], &p[2], &p[0])) &&
+            sign(orient2d(&p[0], &p[1], &p[2])) == -sign(orient2d(&p[1], &p[0], &p[2])) &&
+            sign(incircle(&p[0], &p[1], &p[2], &p[3])) == sign(incircle(&p[1], &p[2], &p[0], &p[3])) &&
+            sign(incircle(&p[0], &p[1], &p[2], &p[3])) == -sign(incircle(&p[1], &p[0], &p[2], &p[3]))
+        }
+        quickcheck(signs_are_invariant_under_cyclic_permutation as fn(coordinates: Vec<i8>) -> bool)
+    }
+
+    #[test]
+    fn batches_match_single_evaluations() {
+        fn batches_match_single_evaluations(coordinates: Vec<(i8, i8, i8)>) -> bool {
+            let p2 = coordinates.iter().map(|&(x, y, _)| Point2D::new(x as f64 * 0.1, y as f64 * 0.1)).collect::<Vec<_>>();
+            let p3 = coordinates.iter().map(|&(x, y, z)| Point3D::new(x as f64 * 0.1, y as f64 * 0.1, z as f64 * 0.1)).collect::<Vec<_>>();
+            if p2.len() < 5 {
+                return true;
+            }
+
+            let mut out = vec!(0.0; p2.len());
+            let same = |out: &[f64], single: &dyn Fn(usize) -> f64| (0..out.len()).all(|i| out[i].to_bits() == single(i).to_bits());
+
+            orient2d_batch(&p2[0], &p2[1], &p2, &mut out);
+            let o2 = same(&out, &|i| orient2d(&p2[0], &p2[1], &p2[i]));
+            incircle_batch(&p2[0], &p2[1], &p2[2], &p2, &mut out);
+            let ic = same(&out, &|i| incircle(&p2[0], &p2[1], &p2[2], &p2[i]));
+            orient3d_batch(&p3[0], &p3[1], &p3[2], &p3, &mut out);
+            let o3 = same(&out, &|i| orient3d(&p3[0], &p3[1], &p3[2], &p3[i]));
+            insphere_batch(&p3[0], &p3[1], &p3[2], &p3[3], &p3, &mut out);
+            let is = same(&out, &|i| insphere(&p3[0], &p3[1], &p3[2], &p3[3], &p3[i]));
+
+            o2 && ic && o3 && is
+        }
+        quickcheck(batches_match_single_evaluations as fn(coordinates: Vec<(i8, i8, i8)>) -> bool)
+    }
+
+    #[test]
+    fn power_tests_are_exact() {
+        fn power_tests_are_exact(c: Vec<(i8, i8, i8, i8)>, tiny: i8) -> bool {
+            if c.len() < 5 {
+                return true;
+            }
+
+            // Weighted grid points are often exactly degenerate, and nearly so with a tiny weight
+            let p = c.iter().enumerate().map(|(i, &(x, y, z, w))| {
+                let tiny = if i == 4 { tiny as f64 * 1e-17 } else { 0.0 };
+                (Point3D::new((x % 4) as f64 * 0.1, (y % 4) as f64 * 0.1, (z % 4) as f64 * 0.1), (w % 4) as f64 * 0.01 + tiny)
+            }).collect::<Vec<_>>();
+            let w2 = p.iter().map(|&(q, w)| Weighted::new(Point2D::new(q.x, q.y), w)).collect::<Vec<_>>();
+            let w3 = p.iter().map(|&(q, w)| Weighted::new(q, w)).collect::<Vec<_>>();
+            let sign = |x: f64| x.partial_cmp(&0.0).unwrap();
+
+            let exact_2d = power_test_exact(&[([p[0].0.x, p[0].0.y], p[0].1), ([p[1].0.x, p[1].0.y], p[1].1),
+                                              ([p[2].0.x, p[2].0.y], p[2].1), ([p[4].0.x, p[4].0.y], p[4].1)]);
+            let exact_3d = power_test_exact(&[([p[0].0.x, p[0].0.y, p[0].0.z], p[0].1), ([p[1].0.x, p[1].0.y, p[1].0.z], p[1].1),
+                                              ([p[2].0.x, p[2].0.y, p[2].0.z], p[2].1), ([p[3].0.x, p[3].0.y, p[3].0.z], p[3].1),
+                                              ([p[4].0.x, p[4].0.y, p[4].0.z], p[4].1)]);
+            let unweighted = |w: &Weighted<Point2D>| Weighted::new(w.point, 0.5);
+
+            sign(power_test_2d(&w2[0], &w2[1], &w2[2], &w2[4])) == sign(exact_2d)
+                && sign(power_test_3d(&w3[0], &w3[1], &w3[2], &w3[3], &w3[4])) == sign(exact_3d)
+                && sign(power_test_2d(&unweighted(&w2[0]), &unweighted(&w2[1]), &unweighted(&w2[2]), &unweighted(&w2[4])))
+                    == sign(incircle(&w2[0].point, &w2[1].point, &w2[2].point, &w2[4].point))
+        }
+        quickcheck(power_tests_are_exact as fn(c: Vec<(i8, i8, i8, i8)>, tiny: i8) -> bool)
+    }
+}
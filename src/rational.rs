@@ -0,0 +1,711 @@
+//! Exact rational arithmetic, as a reference implementation of the predicates and for exact
+//! constructions. Enabled by the `rational` feature.
+//!
+//! Every finite `f64` is a rational number, so the predicates here evaluate the determinants
+//! without any rounding. They are much slower than the adaptive predicates, but simple enough to
+//! be obviously correct, which makes them an oracle to check the adaptive predicates and the
+//! algorithms built on them against.
+
+use geometry::*;
+
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+/// An arbitrary precision integer, stored as its sign and the 32 bit limbs of its magnitude.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Little-endian limbs without trailing zeros, so that zero has none.
+    magnitude: Vec<u32>,
+}
+
+fn trim(mut a: Vec<u32>) -> Vec<u32> {
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+    a
+}
+
+fn bit_length(a: &[u32]) -> usize {
+    a.last().map_or(0, |&top| 32 * a.len() - top.leading_zeros() as usize)
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+
+    for (i, &x) in long.iter().enumerate() {
+        let s = u64::from(x) + u64::from(short.get(i).cloned().unwrap_or(0)) + carry;
+        sum.push(s as u32);
+        carry = s >> 32;
+    }
+    sum.push(carry as u32);
+
+    trim(sum)
+}
+
+/// `a -= b`, for `a >= b`.
+fn subtract_assign(a: &mut Vec<u32>, b: &[u32]) {
+    let mut borrow = 0i64;
+
+    for (i, x) in a.iter_mut().enumerate() {
+        let d = i64::from(*x) - i64::from(b.get(i).cloned().unwrap_or(0)) - borrow;
+        *x = d as u32;
+        borrow = if d < 0 { 1 } else { 0 };
+    }
+    debug_assert_eq!(borrow, 0);
+
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+}
+
+fn multiply(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut product = vec!(0u32; a.len() + b.len());
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let p = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+            product[i + j] = p as u32;
+            carry = p >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+
+    trim(product)
+}
+
+fn shift_left(a: &[u32], bits: usize) -> Vec<u32> {
+    let (limbs, bits) = (bits / 32, bits % 32);
+    let mut shifted = vec!(0u32; limbs);
+
+    if bits == 0 {
+        shifted.extend_from_slice(a);
+    } else {
+        let mut carry = 0;
+        for &x in a {
+            shifted.push(x << bits | carry);
+            carry = x >> (32 - bits);
+        }
+        shifted.push(carry);
+    }
+
+    trim(shifted)
+}
+
+/// `a >>= 1`.
+fn halve(a: &mut Vec<u32>) {
+    for i in 0..a.len() {
+        let high = a.get(i + 1).map_or(0, |&x| x << 31);
+        a[i] = a[i] >> 1 | high;
+    }
+
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+}
+
+/// The quotient and remainder of `a / b`, by binary long division.
+fn divide(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!b.is_empty(), "division by zero");
+
+    let mut remainder = a.to_vec();
+    if compare(a, b) == Ordering::Less {
+        return (Vec::new(), remainder);
+    }
+
+    let shift = bit_length(a) - bit_length(b);
+    let mut divisor = shift_left(b, shift);
+    let mut quotient = vec!(0u32; shift / 32 + 1);
+
+    for i in (0..=shift).rev() {
+        if compare(&remainder, &divisor) != Ordering::Less {
+            subtract_assign(&mut remainder, &divisor);
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+        halve(&mut divisor);
+    }
+
+    (trim(quotient), remainder)
+}
+
+fn gcd(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    while !b.is_empty() {
+        let (_, r) = divide(&a, &b);
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl BigInt {
+    fn from_magnitude(negative: bool, magnitude: Vec<u32>) -> BigInt {
+        let magnitude = trim(magnitude);
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+
+    /// Compares the value with zero.
+    pub fn sign(&self) -> Ordering {
+        match (self.negative, self.magnitude.is_empty()) {
+            (true, _) => Ordering::Less,
+            (false, true) => Ordering::Equal,
+            (false, false) => Ordering::Greater,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_magnitude(false, self.magnitude.clone())
+    }
+
+    /// The quotient rounded towards zero and the remainder, which has the sign of `self`.
+    /// Returns `None` if `other` is zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let (q, r) = divide(&self.magnitude, &other.magnitude);
+        Some((BigInt::from_magnitude(self.negative != other.negative, q), BigInt::from_magnitude(self.negative, r)))
+    }
+
+    /// The greatest common divisor of the absolute values.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        BigInt::from_magnitude(false, gcd(&self.magnitude, &other.magnitude))
+    }
+
+    /// The value multiplied by `2^bits`.
+    pub fn shl(&self, bits: usize) -> BigInt {
+        BigInt::from_magnitude(self.negative, shift_left(&self.magnitude, bits))
+    }
+
+    /// The number of bits of the absolute value.
+    pub fn bits(&self) -> usize {
+        bit_length(&self.magnitude)
+    }
+
+    /// The absolute value, if it fits into 128 bits.
+    fn to_u128(&self) -> Option<u128> {
+        if self.magnitude.len() > 4 {
+            return None;
+        }
+        Some(self.magnitude.iter().rev().fold(0u128, |x, &limb| x << 32 | u128::from(limb)))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(x: i64) -> BigInt {
+        let m = x.unsigned_abs();
+        BigInt::from_magnitude(x < 0, vec!(m as u32, (m >> 32) as u32))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => compare(&self.magnitude, &other.magnitude),
+            (true, true) => compare(&other.magnitude, &self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_magnitude(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl<'b> Add<&'b BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &'b BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_magnitude(self.negative, add(&self.magnitude, &other.magnitude));
+        }
+
+        match compare(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                let mut m = other.magnitude.clone();
+                subtract_assign(&mut m, &self.magnitude);
+                BigInt::from_magnitude(other.negative, m)
+            }
+            _ => {
+                let mut m = self.magnitude.clone();
+                subtract_assign(&mut m, &other.magnitude);
+                BigInt::from_magnitude(self.negative, m)
+            }
+        }
+    }
+}
+
+impl<'b> Sub<&'b BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &'b BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl<'b> Mul<&'b BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &'b BigInt) -> BigInt {
+        BigInt::from_magnitude(self.negative != other.negative, multiply(&self.magnitude, &other.magnitude))
+    }
+}
+
+/// A rational number in lowest terms, with a positive denominator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    /// The fraction `numerator / denominator`, or `None` if the denominator is zero.
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Rational> {
+        if denominator.is_zero() {
+            return None;
+        }
+
+        let g = numerator.gcd(&denominator);
+        let (mut numerator, _) = numerator.div_rem(&g)?;
+        let (mut denominator, _) = denominator.div_rem(&g)?;
+        if denominator.negative {
+            numerator = -&numerator;
+            denominator = -&denominator;
+        }
+
+        Some(Rational { numerator, denominator })
+    }
+
+    /// The exact value of a float, or `None` if it is not finite.
+    pub fn from_f64(x: f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return None;
+        }
+
+        let bits = x.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = if exponent == 0 { (fraction, -1074) } else { (fraction | 1 << 52, exponent - 1075) };
+
+        let mantissa = BigInt::from(if x < 0.0 { -(mantissa as i64) } else { mantissa as i64 });
+        if exponent >= 0 {
+            Rational::new(mantissa.shl(exponent as usize), BigInt::from(1))
+        } else {
+            Rational::new(mantissa, BigInt::from(1).shl(-exponent as usize))
+        }
+    }
+
+    /// The nearest float, rounding ties to even. Results in the subnormal range may be rounded
+    /// twice.
+    pub fn to_f64(&self) -> f64 {
+        if self.numerator.is_zero() {
+            return 0.0;
+        }
+
+        // Scale the quotient to 65 or 66 bits, so that it rounds like the exact value once the
+        // discarded remainder is folded into its lowest bit.
+        let shift = 65 + self.denominator.bits() as i64 - self.numerator.bits() as i64;
+        let (n, d) = if shift >= 0 {
+            (self.numerator.abs().shl(shift as usize), self.denominator.clone())
+        } else {
+            (self.numerator.abs(), self.denominator.shl(-shift as usize))
+        };
+        let (q, r) = n.div_rem(&d).unwrap();
+        let q = q.to_u128().unwrap() | if r.is_zero() { 0 } else { 1 };
+
+        let mut x = q as f64;
+        let mut e = -shift;
+        while e > 0 {
+            let step = e.min(1000);
+            x *= 2.0f64.powi(step as i32);
+            e -= step;
+        }
+        while e < 0 {
+            let step = e.max(-1000);
+            x *= 2.0f64.powi(step as i32);
+            e -= step;
+        }
+
+        if self.numerator.negative { -x } else { x }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    /// Compares the value with zero.
+    pub fn sign(&self) -> Ordering {
+        self.numerator.sign()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    /// The reciprocal, or `None` for zero.
+    pub fn recip(&self) -> Option<Rational> {
+        Rational::new(self.denominator.clone(), self.numerator.clone())
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(x: i64) -> Rational {
+        Rational { numerator: BigInt::from(x), denominator: BigInt::from(1) }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational { numerator: -&self.numerator, denominator: self.denominator.clone() }
+    }
+}
+
+impl<'b> Add<&'b Rational> for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &'b Rational) -> Rational {
+        let numerator = &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator);
+        Rational::new(numerator, &self.denominator * &other.denominator).unwrap()
+    }
+}
+
+impl<'b> Sub<&'b Rational> for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &'b Rational) -> Rational {
+        self + &-other
+    }
+}
+
+impl<'b> Mul<&'b Rational> for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &'b Rational) -> Rational {
+        Rational::new(&self.numerator * &other.numerator, &self.denominator * &other.denominator).unwrap()
+    }
+}
+
+impl<'b> Div<&'b Rational> for &Rational {
+    type Output = Rational;
+
+    /// Panics if `other` is zero.
+    fn div(self, other: &'b Rational) -> Rational {
+        Rational::new(&self.numerator * &other.denominator, &self.denominator * &other.numerator).expect("division by zero")
+    }
+}
+
+macro_rules! forward_binop {
+    ($t:ident, $imp:ident, $method:ident) => {
+        impl $imp for $t {
+            type Output = $t;
+
+            fn $method(self, other: $t) -> $t {
+                (&self).$method(&other)
+            }
+        }
+
+        impl<'a> $imp<&'a $t> for $t {
+            type Output = $t;
+
+            fn $method(self, other: &'a $t) -> $t {
+                (&self).$method(other)
+            }
+        }
+
+        impl<'a> $imp<$t> for &'a $t {
+            type Output = $t;
+
+            fn $method(self, other: $t) -> $t {
+                self.$method(&other)
+            }
+        }
+    }
+}
+
+forward_binop!(BigInt, Add, add);
+forward_binop!(BigInt, Sub, sub);
+forward_binop!(BigInt, Mul, mul);
+forward_binop!(Rational, Add, add);
+forward_binop!(Rational, Sub, sub);
+forward_binop!(Rational, Mul, mul);
+forward_binop!(Rational, Div, div);
+
+impl Point2D<Rational> {
+    /// The exact point, or `None` if a coordinate is not finite.
+    pub fn from_f64(p: &Point2D) -> Option<Point2D<Rational>> {
+        Some(Point2D::new(Rational::from_f64(p.x)?, Rational::from_f64(p.y)?))
+    }
+
+    /// The nearest point with `f64` coordinates.
+    pub fn to_f64(&self) -> Point2D {
+        Point2D::new(self.x.to_f64(), self.y.to_f64())
+    }
+}
+
+impl Point3D<Rational> {
+    /// The exact point, or `None` if a coordinate is not finite.
+    pub fn from_f64(p: &Point3D) -> Option<Point3D<Rational>> {
+        Some(Point3D::new(Rational::from_f64(p.x)?, Rational::from_f64(p.y)?, Rational::from_f64(p.z)?))
+    }
+
+    /// The nearest point with `f64` coordinates.
+    pub fn to_f64(&self) -> Point3D {
+        Point3D::new(self.x.to_f64(), self.y.to_f64(), self.z.to_f64())
+    }
+}
+
+fn det2(a: &Rational, b: &Rational, c: &Rational, d: &Rational) -> Rational {
+    a * d - b * c
+}
+
+fn det3(m: [[&Rational; 3]; 3]) -> Rational {
+    m[0][0] * &det2(m[1][1], m[1][2], m[2][1], m[2][2])
+        - m[0][1] * &det2(m[1][0], m[1][2], m[2][0], m[2][2])
+        + m[0][2] * &det2(m[1][0], m[1][1], m[2][0], m[2][1])
+}
+
+fn lift_2d(x: &Rational, y: &Rational) -> Rational {
+    x * x + y * y
+}
+
+fn lift_3d(x: &Rational, y: &Rational, z: &Rational) -> Rational {
+    x * x + y * y + z * z
+}
+
+/// The sign of the determinant of `orient2d`, `Greater` for counter-clockwise points.
+pub fn orient2d(pa: &Point2D<Rational>, pb: &Point2D<Rational>, pc: &Point2D<Rational>) -> Ordering {
+    det2(&(&pa.x - &pc.x), &(&pa.y - &pc.y), &(&pb.x - &pc.x), &(&pb.y - &pc.y)).sign()
+}
+
+/// The sign of the determinant of `orient3d`.
+pub fn orient3d(pa: &Point3D<Rational>, pb: &Point3D<Rational>, pc: &Point3D<Rational>, pd: &Point3D<Rational>) -> Ordering {
+    let d = |p: &Point3D<Rational>| [&p.x - &pd.x, &p.y - &pd.y, &p.z - &pd.z];
+    let (a, b, c) = (d(pa), d(pb), d(pc));
+
+    det3([[&a[0], &a[1], &a[2]], [&b[0], &b[1], &b[2]], [&c[0], &c[1], &c[2]]]).sign()
+}
+
+/// The sign of the determinant of `incircle`, `Greater` if `pd` lies inside the circle through
+/// the other points in counter-clockwise order.
+pub fn incircle(pa: &Point2D<Rational>, pb: &Point2D<Rational>, pc: &Point2D<Rational>, pd: &Point2D<Rational>) -> Ordering {
+    let d = |p: &Point2D<Rational>| {
+        let (x, y) = (&p.x - &pd.x, &p.y - &pd.y);
+        let lift = lift_2d(&x, &y);
+        [x, y, lift]
+    };
+    let (a, b, c) = (d(pa), d(pb), d(pc));
+
+    det3([[&a[0], &a[1], &a[2]], [&b[0], &b[1], &b[2]], [&c[0], &c[1], &c[2]]]).sign()
+}
+
+/// The sign of the determinant of `insphere`, `Greater` if `pe` lies inside the sphere through
+/// the other points, when `orient3d` of those is positive.
+pub fn insphere(pa: &Point3D<Rational>, pb: &Point3D<Rational>, pc: &Point3D<Rational>, pd: &Point3D<Rational>, pe: &Point3D<Rational>) -> Ordering {
+    let d = |p: &Point3D<Rational>| {
+        let (x, y, z) = (&p.x - &pe.x, &p.y - &pe.y, &p.z - &pe.z);
+        let lift = lift_3d(&x, &y, &z);
+        [x, y, z, lift]
+    };
+    let rows = [d(pa), d(pb), d(pc), d(pd)];
+
+    // Expansion along the lifted column, with the minors of the remaining rows
+    let minor = |skip: usize| {
+        let r = (0..4).filter(|&i| i != skip).map(|i| &rows[i]).collect::<Vec<_>>();
+        det3([[&r[0][0], &r[0][1], &r[0][2]], [&r[1][0], &r[1][1], &r[1][2]], [&r[2][0], &r[2][1], &r[2][2]]])
+    };
+    let det = &rows[1][3] * &minor(1) - &rows[0][3] * &minor(0) + &rows[3][3] * &minor(3) - &rows[2][3] * &minor(2);
+
+    det.sign()
+}
+
+/// The exact center of the circle through the points, or `None` if they are collinear.
+pub fn circumcenter_2d(pa: &Point2D<Rational>, pb: &Point2D<Rational>, pc: &Point2D<Rational>) -> Option<Point2D<Rational>> {
+    let (bx, by) = (&pb.x - &pa.x, &pb.y - &pa.y);
+    let (cx, cy) = (&pc.x - &pa.x, &pc.y - &pa.y);
+    let (b2, c2) = (lift_2d(&bx, &by), lift_2d(&cx, &cy));
+    let d = (det2(&bx, &by, &cx, &cy) * Rational::from(2)).recip()?;
+
+    Some(Point2D::new(&pa.x + &((&cy * &b2 - &by * &c2) * &d),
+                      &pa.y + &((&bx * &c2 - &cx * &b2) * &d)))
+}
+
+/// The exact center of the sphere through the points, or `None` if they are coplanar.
+pub fn circumcenter_3d(pa: &Point3D<Rational>, pb: &Point3D<Rational>, pc: &Point3D<Rational>, pd: &Point3D<Rational>) -> Option<Point3D<Rational>> {
+    let d = |p: &Point3D<Rational>| [&p.x - &pa.x, &p.y - &pa.y, &p.z - &pa.z];
+    let (a, b, c) = (d(pb), d(pc), d(pd));
+    let lifts = [lift_3d(&a[0], &a[1], &a[2]), lift_3d(&b[0], &b[1], &b[2]), lift_3d(&c[0], &c[1], &c[2])];
+
+    // Cramer's rule on 2 (p - pa) . center = |p - pa|^2
+    let m = [&a, &b, &c];
+    let det = det3([[&m[0][0], &m[0][1], &m[0][2]], [&m[1][0], &m[1][1], &m[1][2]], [&m[2][0], &m[2][1], &m[2][2]]]);
+    let scale = (det * Rational::from(2)).recip()?;
+    let column = |k: usize| {
+        let entry = |i: usize, j: usize| if j == k { &lifts[i] } else { &m[i][j] };
+        det3([[entry(0, 0), entry(0, 1), entry(0, 2)], [entry(1, 0), entry(1, 1), entry(1, 2)], [entry(2, 0), entry(2, 1), entry(2, 2)]]) * &scale
+    };
+
+    Some(Point3D::new(&pa.x + &column(0), &pa.y + &column(1), &pa.z + &column(2)))
+}
+
+/// The exact intersection of the lines through `pa`, `pb` and through `pc`, `pd`, or `None` if
+/// they are parallel or a line is degenerate.
+pub fn line_intersection(pa: &Point2D<Rational>, pb: &Point2D<Rational>, pc: &Point2D<Rational>, pd: &Point2D<Rational>) -> Option<Point2D<Rational>> {
+    let (ux, uy) = (&pb.x - &pa.x, &pb.y - &pa.y);
+    let (vx, vy) = (&pd.x - &pc.x, &pd.y - &pc.y);
+    let (wx, wy) = (&pc.x - &pa.x, &pc.y - &pa.y);
+    let t = det2(&wx, &wy, &vx, &vy) * det2(&ux, &uy, &vx, &vy).recip()?;
+
+    Some(Point2D::new(&pa.x + &(&ux * &t), &pa.y + &(&uy * &t)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use predicates;
+    use quickcheck::{TestResult, quickcheck};
+
+    fn sign(x: f64) -> Ordering {
+        x.partial_cmp(&0.0).unwrap()
+    }
+
+    fn exact_2d(p: &Point2D) -> Point2D<Rational> {
+        Point2D::<Rational>::from_f64(p).unwrap()
+    }
+
+    fn exact_3d(p: &Point3D) -> Point3D<Rational> {
+        Point3D::<Rational>::from_f64(p).unwrap()
+    }
+
+    #[test]
+    fn integer_arithmetic_matches_i128() {
+        fn integer_arithmetic_matches_i128(a: i64, b: i64, c: i64) -> TestResult {
+            if c == 0 {
+                return TestResult::discard();
+            }
+
+            let (x, y, z) = (BigInt::from(a), BigInt::from(b), BigInt::from(c));
+            let wide = |x: &BigInt| x.to_u128().map(|m| if x.negative { -(m as i128) } else { m as i128 });
+            let (q, r) = (&x * &y).div_rem(&z).unwrap();
+
+            TestResult::from_bool(wide(&(&x + &y)) == Some(a as i128 + b as i128)
+                && wide(&(&x - &y)) == Some(a as i128 - b as i128)
+                && wide(&(&x * &y)) == Some(a as i128 * b as i128)
+                && wide(&q) == Some(a as i128 * b as i128 / c as i128)
+                && wide(&r) == Some(a as i128 * b as i128 % c as i128)
+                && x.cmp(&y) == a.cmp(&b))
+        }
+        quickcheck(integer_arithmetic_matches_i128 as fn(a: i64, b: i64, c: i64) -> TestResult)
+    }
+
+    #[test]
+    fn floats_round_trip() {
+        fn floats_round_trip(x: f64, y: f64) -> bool {
+            let (a, b) = (Rational::from_f64(x).unwrap(), Rational::from_f64(y).unwrap());
+
+            a.to_f64() == x && (&a + &b).to_f64() == x + y && (&a * &b).to_f64() == x * y
+                && (y == 0.0 || (&a / &b).to_f64() == x / y) && a.cmp(&b) == x.partial_cmp(&y).unwrap()
+        }
+        quickcheck(floats_round_trip as fn(x: f64, y: f64) -> bool);
+
+        for &x in &[0.1, -1e300, 1e-300, 5e-324, f64::MAX, f64::MIN_POSITIVE, 2.0f64.powi(-1022) * 1.5] {
+            assert_eq!(Rational::from_f64(x).unwrap().to_f64(), x);
+        }
+        assert_eq!(Rational::from_f64(f64::NAN), None);
+        assert_eq!((Rational::from(1) / Rational::from(3)).to_f64(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn predicates_match_the_adaptive_predicates() {
+        fn predicates_match_the_adaptive_predicates(c: Vec<(i8, i8, i8)>, tiny: (i8, i8, i8)) -> TestResult {
+            if c.len() < 5 {
+                return TestResult::discard();
+            }
+
+            // Nearly degenerate points on a coarse grid, with one of them moved by a few ulps
+            let p3 = (0..5).map(|i| {
+                let (x, y, z) = c[i];
+                let p = Point3D::new((x % 3) as f64 * 0.1, (y % 3) as f64 * 0.1, (z % 3) as f64 * 0.1);
+                if i == 4 { Point3D::new(p.x + tiny.0 as f64 * 1e-17, p.y + tiny.1 as f64 * 1e-17, p.z + tiny.2 as f64 * 1e-17) } else { p }
+            }).collect::<Vec<_>>();
+            let p2 = p3.iter().map(|p| Point2D::new(p.x, p.y)).collect::<Vec<_>>();
+            let (e2, e3) = (p2.iter().map(exact_2d).collect::<Vec<_>>(), p3.iter().map(exact_3d).collect::<Vec<_>>());
+
+            TestResult::from_bool(
+                orient2d(&e2[0], &e2[1], &e2[4]) == sign(predicates::orient2d(&p2[0], &p2[1], &p2[4]))
+                && orient3d(&e3[0], &e3[1], &e3[2], &e3[4]) == sign(predicates::orient3d(&p3[0], &p3[1], &p3[2], &p3[4]))
+                && incircle(&e2[0], &e2[1], &e2[2], &e2[4]) == sign(predicates::incircle(&p2[0], &p2[1], &p2[2], &p2[4]))
+                && insphere(&e3[0], &e3[1], &e3[2], &e3[3], &e3[4]) == sign(predicates::insphere(&p3[0], &p3[1], &p3[2], &p3[3], &p3[4])))
+        }
+        quickcheck(predicates_match_the_adaptive_predicates as fn(c: Vec<(i8, i8, i8)>, tiny: (i8, i8, i8)) -> TestResult)
+    }
+
+    #[test]
+    fn constructions_are_exact() {
+        let (a, b, c) = (exact_2d(&Point2D::new(0.1, 0.2)), exact_2d(&Point2D::new(1.3, -0.7)), exact_2d(&Point2D::new(-0.4, 2.9)));
+        let center = circumcenter_2d(&a, &b, &c).unwrap();
+        let distance = |p: &Point2D<Rational>| lift_2d(&(&p.x - &center.x), &(&p.y - &center.y));
+        assert_eq!(distance(&a), distance(&b));
+        assert_eq!(distance(&a), distance(&c));
+        let half = Rational::from_f64(0.5).unwrap();
+        let midpoint = Point2D::new((&a.x + &b.x) * &half, (&a.y + &b.y) * &half);
+        assert_eq!(circumcenter_2d(&a, &b, &midpoint), None);
+
+        let p = [Point3D::new(0.1, 0.2, 0.3), Point3D::new(1.0, -0.5, 0.25), Point3D::new(-0.75, 0.1, 1.0), Point3D::new(0.3, 0.9, -0.6)];
+        let e = p.iter().map(exact_3d).collect::<Vec<_>>();
+        let center = circumcenter_3d(&e[0], &e[1], &e[2], &e[3]).unwrap();
+        let distance = |p: &Point3D<Rational>| lift_3d(&(&p.x - &center.x), &(&p.y - &center.y), &(&p.z - &center.z));
+        assert!(e.iter().all(|p| distance(p) == distance(&e[0])));
+        assert!((center.to_f64().x - Tetrahedron::new(p[0], p[1], p[2], p[3]).circumcenter().x).abs() < 1e-12);
+
+        let d = exact_2d(&Point2D::new(0.7, 0.6));
+        let x = line_intersection(&a, &b, &c, &d).unwrap();
+        assert_eq!(orient2d(&a, &b, &x), Ordering::Equal);
+        assert_eq!(orient2d(&c, &d, &x), Ordering::Equal);
+        assert_eq!(line_intersection(&a, &b, &a, &b), None);
+    }
+}
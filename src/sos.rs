@@ -0,0 +1,245 @@
+//! Orientation and in-circle tests with Simulation of Simplicity, the symbolic perturbation
+//! scheme of Edelsbrunner and Mücke.
+//!
+//! Each coordinate of the point with index `i` is moved by a distinct, infinitesimally small
+//! amount, and the lifted coordinate `x^2 + y^2 (+ z^2)` of the in-circle tests is perturbed
+//! independently. Points with lower indices are perturbed more. The perturbed points are in
+//! general position, so the tests never return a degenerate result. Since the perturbation
+//! only depends on the indices, all tests on the same slice of points are consistent with each
+//! other, e.g. a triangulation built from them is valid.
+//!
+//! The tests return `None` if an index is out of bounds, the indices are not distinct, or a
+//! coordinate is not finite.
+
+use geometry::*;
+use in_circle::*;
+use expansion::{Expansion, determinant};
+use predicates::{orient2d, orient3d, incircle, insphere};
+
+use std::cmp::Ordering;
+
+/// The orientation of the triangle of the points with the given indices.
+pub fn orientation_2d_sos(points: &[Point2D], i: usize, j: usize, k: usize) -> Option<Orientation> {
+    let p = fetch(points, &[i, j, k])?;
+    let det = orient2d(&p[0], &p[1], &p[2]);
+
+    perturbed_sign(det, &[i, j, k], |v| lifted_2d(&points[v], false)).map(to_orientation)
+}
+
+/// The orientation of the tetrahedron of the points with the given indices.
+pub fn orientation_3d_sos(points: &[Point3D], i: usize, j: usize, k: usize, l: usize) -> Option<Orientation> {
+    let p = fetch(points, &[i, j, k, l])?;
+    let det = orient3d(&p[0], &p[1], &p[2], &p[3]);
+
+    perturbed_sign(det, &[i, j, k, l], |v| lifted_3d(&points[v], false)).map(to_orientation)
+}
+
+/// Tests the point with index `l` against the circumcircle of the triangle with the indices `i`,
+/// `j` and `k`. The result is never `InCircleLocation::On`.
+pub fn in_circle_sos(points: &[Point2D], i: usize, j: usize, k: usize, l: usize) -> Option<InCircleLocation> {
+    let p = fetch(points, &[i, j, k, l])?;
+    let det = incircle(&p[0], &p[1], &p[2], &p[3]);
+
+    let location = perturbed_sign(det, &[i, j, k, l], |v| lifted_2d(&points[v], true))?;
+    let orientation = perturbed_sign(orient2d(&p[0], &p[1], &p[2]), &[i, j, k], |v| lifted_2d(&points[v], false))?;
+
+    Some(to_location(location, orientation))
+}
+
+/// Tests the point with index `m` against the circumsphere of the tetrahedron with the indices
+/// `i`, `j`, `k` and `l`. The result is never `InCircleLocation::On`.
+pub fn in_sphere_sos(points: &[Point3D], i: usize, j: usize, k: usize, l: usize, m: usize) -> Option<InCircleLocation> {
+    let p = fetch(points, &[i, j, k, l, m])?;
+    let det = insphere(&p[0], &p[1], &p[2], &p[3], &p[4]);
+
+    let location = perturbed_sign(det, &[i, j, k, l, m], |v| lifted_3d(&points[v], true))?;
+    let orientation = perturbed_sign(orient3d(&p[0], &p[1], &p[2], &p[3]), &[i, j, k, l], |v| lifted_3d(&points[v], false))?;
+
+    Some(to_location(location, orientation))
+}
+
+fn fetch<P: Copy>(points: &[P], indices: &[usize]) -> Option<Vec<P>> {
+    indices.iter().map(|&i| points.get(i).cloned()).collect()
+}
+
+fn to_orientation(sign: Ordering) -> Orientation {
+    // A positive orientation is clockwise, i.e. a negative determinant
+    match sign {
+        Ordering::Less => Orientation::Positive,
+        _ => Orientation::Negative,
+    }
+}
+
+fn to_location(location: Ordering, orientation: Ordering) -> InCircleLocation {
+    if location == orientation {
+        InCircleLocation::Inside
+    } else {
+        InCircleLocation::Outside
+    }
+}
+
+/// The row of a point in the determinant, without the trailing one.
+fn lifted_2d(p: &Point2D, lift: bool) -> Vec<Expansion> {
+    let mut row = vec!(Expansion::new(p.x), Expansion::new(p.y));
+    if lift {
+        row.push(Expansion::product(p.x, p.x) + Expansion::product(p.y, p.y));
+    }
+    row
+}
+
+fn lifted_3d(p: &Point3D, lift: bool) -> Vec<Expansion> {
+    let mut row = vec!(Expansion::new(p.x), Expansion::new(p.y), Expansion::new(p.z));
+    if lift {
+        row.push(Expansion::product(p.x, p.x) + Expansion::product(p.y, p.y) + Expansion::product(p.z, p.z));
+    }
+    row
+}
+
+/// The sign of the determinant with the rows `(row(v), 1)` for the given indices, under the
+/// symbolic perturbation. `det` is the unperturbed determinant, which settles the sign in all
+/// but degenerate cases.
+fn perturbed_sign<F: Fn(usize) -> Vec<Expansion>>(det: f64, indices: &[usize], row: F) -> Option<Ordering> {
+    if !det.is_finite() {
+        return None;
+    }
+    if det != 0.0 {
+        return det.partial_cmp(&0.0);
+    }
+
+    // Sort the rows by index, keeping track of the sign of the permutation
+    let mut sorted = indices.to_vec();
+    let mut flipped = false;
+    for a in 0..sorted.len() {
+        for b in (a + 1)..sorted.len() {
+            if sorted[a] > sorted[b] {
+                sorted.swap(a, b);
+                flipped = !flipped;
+            } else if sorted[a] == sorted[b] {
+                return None;
+            }
+        }
+    }
+
+    let rows = sorted.iter().map(|&v| row(v)).collect::<Vec<_>>();
+    let d = rows[0].len();
+
+    // Moving the entry in row `r` and column `c` by the perturbation of bit `b = r * d + d - 1 - c`
+    // adds a multiple of eps^(2^b), so the perturbation terms are ordered like the binary
+    // numbers of their sets of bits. The coefficient of a term is the determinant with its rows
+    // replaced by unit vectors. It is zero if two bits share a row or column, and the term with
+    // one bit in each of the first `d` rows always has a non-zero coefficient.
+    for mask in 1u32..(1 << (d * d)) {
+        let bits = (0..d * d).filter(|&b| mask & (1 << b) != 0).map(|b| (b / d, d - 1 - b % d)).collect::<Vec<_>>();
+        let conflicting = bits.iter().enumerate().any(|(n, &(r, c))| {
+            bits[..n].iter().any(|&(r2, c2)| r == r2 || c == c2)
+        });
+        if conflicting {
+            continue;
+        }
+
+        let matrix = rows.iter().enumerate().map(|(r, row)| {
+            match bits.iter().find(|&&(r2, _)| r2 == r) {
+                Some(&(_, c)) => (0..=d).map(|k| Expansion::new(if k == c { 1.0 } else { 0.0 })).collect(),
+                None => row.iter().cloned().chain(Some(Expansion::new(1.0))).collect::<Vec<_>>(),
+            }
+        }).collect::<Vec<_>>();
+
+        let sign = determinant(&matrix).sign();
+        if sign != Ordering::Equal {
+            return Some(if flipped { sign.reverse() } else { sign });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn sign(x: f64) -> Ordering {
+        x.partial_cmp(&0.0).unwrap()
+    }
+
+    #[test]
+    fn determinants_match_the_predicates() {
+        let p = [Point3D::new(0.5, 1.0, 3.0), Point3D::new(2.0, -1.0, 0.25), Point3D::new(-3.0, 1.5, 1.0),
+                 Point3D::new(1.0, 2.0, -2.0), Point3D::new(0.125, 0.5, 0.75)];
+        let row = |p: &Point3D, lift: bool| lifted_3d(p, lift).into_iter().chain(Some(Expansion::new(1.0))).collect::<Vec<_>>();
+
+        let matrix = p[..4].iter().map(|p| row(p, false)).collect::<Vec<_>>();
+        assert_eq!(determinant(&matrix).sign(), sign(orient3d(&p[0], &p[1], &p[2], &p[3])));
+
+        let matrix = p.iter().map(|p| row(p, true)).collect::<Vec<_>>();
+        assert_eq!(determinant(&matrix).sign(), sign(insphere(&p[0], &p[1], &p[2], &p[3], &p[4])));
+    }
+
+    #[test]
+    fn collinear_points_get_an_orientation() {
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0), Point2D::new(1.0, 1.0)];
+
+        for &(i, j, k) in &[(0, 1, 2), (0, 1, 3), (1, 3, 2), (2, 0, 3)] {
+            let o = orientation_2d_sos(&points, i, j, k).unwrap();
+            assert_eq!(orientation_2d_sos(&points, j, k, i), Some(o));
+            assert!(orientation_2d_sos(&points, j, i, k).unwrap() != orientation_2d_sos(&points, i, j, k).unwrap());
+        }
+
+        assert_eq!(orientation_2d_sos(&points, 0, 1, 1), None);
+        assert_eq!(orientation_2d_sos(&points, 0, 1, 4), None);
+    }
+
+    #[test]
+    fn cocircular_points_are_never_on_the_circle() {
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 0.0), Point2D::new(1.0, 1.0), Point2D::new(0.0, 1.0)];
+
+        let abc = in_circle_sos(&points, 0, 1, 2, 3).unwrap();
+        let acd = in_circle_sos(&points, 0, 2, 3, 1).unwrap();
+        let abd = in_circle_sos(&points, 0, 1, 3, 2).unwrap();
+
+        // Exactly one of the two diagonals of the square is chosen
+        assert!(abc != InCircleLocation::On);
+        assert_eq!(abc, acd);
+        assert!(abc != abd);
+        assert_eq!(in_circle_sos(&points, 2, 1, 0, 3), Some(abc));
+    }
+
+    #[test]
+    fn cospherical_points_are_never_on_the_sphere() {
+        let points = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 0.0, 0.0), Point3D::new(0.0, 1.0, 0.0),
+                      Point3D::new(0.0, 0.0, 1.0), Point3D::new(1.0, 1.0, 1.0), Point3D::new(1.0, 1.0, 0.0)];
+
+        for m in 4..6 {
+            let location = in_sphere_sos(&points, 0, 1, 2, 3, m).unwrap();
+            assert!(location != InCircleLocation::On);
+            assert_eq!(in_sphere_sos(&points, 1, 0, 2, 3, m), Some(location));
+        }
+        assert!(orientation_3d_sos(&points, 0, 1, 2, 5).is_some());
+        assert!(orientation_3d_sos(&points, 0, 1, 2, 5) != orientation_3d_sos(&points, 1, 0, 2, 5));
+    }
+
+    #[test]
+    fn grid_points_are_consistent() {
+        fn grid_points_are_consistent(coordinates: Vec<(i8, i8)>) -> TestResult {
+            // A coarse grid to get many degeneracies
+            let points = coordinates.iter().map(|&(x, y)| Point2D::new((x % 3) as f64, (y % 3) as f64)).take(5).collect::<Vec<_>>();
+            if points.len() < 4 {
+                return TestResult::discard();
+            }
+
+            let o = |i, j, k| orientation_2d_sos(&points, i, j, k).unwrap();
+            let c = |i, j, k, l| in_circle_sos(&points, i, j, k, l).unwrap();
+
+            // Orientations flip under odd permutations. The in-circle tests do not depend on the
+            // order of the triangle, and for a convex quadrilateral abcd, d lies inside abc
+            // exactly if b lies inside acd.
+            let convex = o(0, 1, 2) == o(0, 2, 3) && o(0, 1, 3) == o(1, 2, 3);
+
+            TestResult::from_bool(o(0, 1, 2) == o(1, 2, 0) && o(0, 1, 2) != o(1, 0, 2) &&
+                                  c(0, 1, 2, 3) == c(1, 0, 2, 3) && c(0, 1, 2, 3) == c(2, 0, 1, 3) &&
+                                  (!convex || c(0, 1, 2, 3) == c(0, 2, 3, 1)))
+        }
+        quickcheck(grid_points_are_consistent as fn(coordinates: Vec<(i8, i8)>) -> TestResult)
+    }
+}
@@ -0,0 +1,117 @@
+//! Counts of the stages the predicates finish in, to tell how degenerate a data set is. Enabled
+//! by the `statistics` feature; without it, the predicates do not count anything.
+//!
+//! The counters are global and shared by all threads. The batch predicates count each of their
+//! points like a single evaluation.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    Orient2d,
+    Orient3d,
+    InCircle,
+    InSphere,
+    Power2d,
+    Power3d,
+    OrientNd,
+    InSphereNd,
+}
+
+/// The stages of an adaptive predicate, from the cheapest to the most expensive one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// The floating-point evaluation with a static error bound.
+    Filter,
+    /// The exact determinant of the rounded coordinate differences, approximated by the sum of
+    /// its components.
+    Estimate,
+    /// The estimate, corrected by the first order terms of the roundoff in the differences.
+    Tails,
+    /// The exact evaluation. The power tests go there directly from the filter.
+    Exact,
+}
+
+impl Predicate {
+    pub const ALL: [Predicate; 8] = [Predicate::Orient2d, Predicate::Orient3d, Predicate::InCircle, Predicate::InSphere,
+                                     Predicate::Power2d, Predicate::Power3d, Predicate::OrientNd, Predicate::InSphereNd];
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Filter, Stage::Estimate, Stage::Tails, Stage::Exact];
+}
+
+static COUNTS: [AtomicU64; 32] = [const { AtomicU64::new(0) }; 32];
+
+fn counter(predicate: Predicate, stage: Stage) -> &'static AtomicU64 {
+    &COUNTS[4 * predicate as usize + stage as usize]
+}
+
+pub(crate) fn record(predicate: Predicate, stage: Stage, count: usize) {
+    counter(predicate, stage).fetch_add(count as u64, Ordering::Relaxed);
+}
+
+/// The number of evaluations of the predicate that finished in the stage.
+pub fn count(predicate: Predicate, stage: Stage) -> u64 {
+    counter(predicate, stage).load(Ordering::Relaxed)
+}
+
+/// The number of evaluations of the predicate.
+pub fn calls(predicate: Predicate) -> u64 {
+    Stage::ALL.iter().map(|&stage| count(predicate, stage)).sum()
+}
+
+/// Sets all counts to zero.
+pub fn reset() {
+    for c in &COUNTS {
+        c.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use geometry::*;
+    use predicates::{orient2d, orient3d, incircle, insphere, power_test_2d, power_test_3d, orient2d_batch,
+                     orient_nd, insphere_nd};
+
+    // Other tests evaluate predicates concurrently, so the counts can only be checked for
+    // increasing by at least the expected amount.
+
+    #[test]
+    fn degenerate_inputs_need_the_exact_stage() {
+        let exact = |p| count(p, Stage::Exact);
+        let before = Predicate::ALL.iter().map(|&p| exact(p)).collect::<Vec<_>>();
+
+        // Exactly degenerate, but the coordinate differences round: points on a diagonal, and the
+        // corners of a box
+        let p2 = [Point2D::new(0.1, 0.2), Point2D::new(0.7, 0.2), Point2D::new(0.7, 0.9), Point2D::new(0.1, 0.9)];
+        let p3 = [Point3D::new(0.1, 0.2, 0.3), Point3D::new(0.7, 0.2, 0.3), Point3D::new(0.1, 0.9, 0.3),
+                  Point3D::new(0.7, 0.9, 0.3), Point3D::new(0.1, 0.2, 0.8)];
+        assert_eq!(orient2d(&Point2D::new(0.1, 0.1), &Point2D::new(0.3, 0.3), &Point2D::new(0.7, 0.7)), 0.0);
+        let tilted = p3.iter().map(|p| Point3D::new(p.x, p.y, p.x)).collect::<Vec<_>>();
+        assert_eq!(orient3d(&tilted[0], &tilted[1], &tilted[2], &tilted[3]), 0.0);
+        assert_eq!(incircle(&p2[0], &p2[1], &p2[2], &p2[3]), 0.0);
+        assert_eq!(insphere(&p3[0], &p3[1], &p3[2], &p3[4], &p3[3]), 0.0);
+        let (w2, w3) = (|i: usize| Weighted::new(p2[i], 0.1), |i: usize| Weighted::new(p3[i], 0.1));
+        assert_eq!(power_test_2d(&w2(0), &w2(1), &w2(2), &w2(3)), 0.0);
+        assert_eq!(power_test_3d(&w3(0), &w3(1), &w3(2), &w3(4), &w3(3)), 0.0);
+        assert_eq!(orient_nd(&tilted.iter().take(4).map(|p| [p.x, p.y, p.z]).collect::<Vec<_>>()), 0.0);
+        assert_eq!(insphere_nd(&p3.iter().map(|p| [p.x, p.y, p.z]).collect::<Vec<_>>()), 0.0);
+
+        for (&p, &b) in Predicate::ALL.iter().zip(&before) {
+            assert!(exact(p) > b, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn every_batch_point_is_counted() {
+        let before = calls(Predicate::Orient2d);
+        let points = (0..100).map(|i| Point2D::new(i as f64 * 0.1, i as f64 * 0.1)).collect::<Vec<_>>();
+        let mut out = vec!(0.0; points.len());
+        orient2d_batch(&Point2D::new(0.0, 0.0), &Point2D::new(1.0, 2.0), &points, &mut out);
+
+        assert!(calls(Predicate::Orient2d) >= before + 100);
+    }
+}
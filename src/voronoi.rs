@@ -0,0 +1,361 @@
+use geometry::*;
+use in_circle::*;
+use delaunay::*;
+use dcel::*;
+use error::GeometryError;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoronoiEdgeKind {
+    /// A finite edge between two Voronoi vertices.
+    Segment(usize, usize),
+    /// A semi-infinite edge starting at a Voronoi vertex, pointing in the given direction.
+    Ray(usize, Point2D),
+    /// An infinite line through the given point, in the given direction. Only separates collinear
+    /// sites, which have no Voronoi vertices.
+    Line(Point2D, Point2D),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoronoiEdge {
+    /// The two sites separated by this edge.
+    pub sites: (VertexHandle, VertexHandle),
+    pub kind: VoronoiEdgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoronoiCell {
+    /// The vertex of the input site of this cell in the triangulation.
+    pub site: VertexHandle,
+    /// The Voronoi vertices of the cell, in counter-clockwise order around the site.
+    pub vertices: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+/// The Voronoi diagram of the sites of a Delaunay triangulation, built as its dual: the
+/// Voronoi vertices are the circumcenters of the triangles, and each Delaunay edge gives a
+/// Voronoi edge. Triangles touching the domain vertices are not part of the triangulation of the
+/// sites, so edges on the convex hull of the sites become rays. Collinear sites have no triangles
+/// at all, and are separated by parallel lines.
+///
+/// A domain vertex can lie inside the circumcircle of a triangle of the sites, e.g. of a flat
+/// triangle on the hull, and then replaces it in the triangulation. The edges of the convex hull
+/// are therefore recovered in a copy of the triangulation first, which leaves only triangles of
+/// the sites inside the hull.
+pub struct Voronoi {
+    vertices: Vec<Point2D>,
+    edges: Vec<VoronoiEdge>,
+    cells: Vec<VoronoiCell>,
+}
+
+impl Voronoi {
+    /// Builds the diagram, or returns `None` if the convex hull of the sites could not be
+    /// recovered.
+    pub fn new(d: &Delaunay<Point2D>) -> Option<Voronoi> {
+        Voronoi::try_new(d).ok()
+    }
+
+    /// Like `new`, but tells why the convex hull could not be recovered.
+    pub fn try_new(d: &Delaunay<Point2D>) -> Result<Voronoi, GeometryError> {
+        let mut d = d.clone();
+        d.constrain_hull()?;
+        let d = &d;
+        let dcel = d.dcel();
+
+        // Only triangles between sites are dual to Voronoi vertices
+        let faces = dcel.faces().into_iter().filter(|&f| {
+            !dcel.face_vertices(f).iter().any(|&v| d.is_domain_vertex(v))
+        }).collect::<Vec<_>>();
+        if faces.is_empty() {
+            return Ok(Voronoi::collinear(d));
+        }
+        let index = faces.iter().enumerate().map(|(i, &f)| (f, i)).collect::<HashMap<_, _>>();
+
+        let vertices = faces.iter().map(|&f| d.triangle(f).circumcenter()).collect::<Vec<_>>();
+        let mut edges = Vec::new();
+        let mut edge_index = HashMap::new();
+
+        for (i, &f) in faces.iter().enumerate() {
+            for e in dcel.face_edges(f) {
+                let (a, b) = (dcel.origin(e), dcel.destination(e));
+
+                match dcel.twin(e).and_then(|t| index.get(&dcel.face(t)).map(|&j| (t, j))) {
+                    // Every shared edge is seen from both sides, only emit it once
+                    Some((t, j)) if t > e => {
+                        edge_index.insert(e, edges.len());
+                        edge_index.insert(t, edges.len());
+                        edges.push(VoronoiEdge { sites: (a, b), kind: VoronoiEdgeKind::Segment(i, j) });
+                    },
+                    Some(_) => {},
+                    None => {
+                        let (pa, pb) = (d.point(a), d.point(b));
+                        let mut direction = Point2D::new(pa.y - pb.y, pb.x - pa.x);
+                        let pc = d.point(dcel.origin(dcel.prev(e)));
+
+                        // Point away from the opposite vertex, i.e. out of the hull
+                        if direction.x * (pc.x - pa.x) + direction.y * (pc.y - pa.y) > 0.0 {
+                            direction = Point2D::new(-direction.x, -direction.y);
+                        }
+
+                        edge_index.insert(e, edges.len());
+                        edges.push(VoronoiEdge { sites: (a, b), kind: VoronoiEdgeKind::Ray(i, direction) });
+                    },
+                }
+            }
+        }
+
+        // The rotation around a vertex follows the orientation of the faces
+        let clockwise = d.domain().orientation() == Some(Orientation::Positive);
+
+        let cells = d.vertices().into_iter().map(|site| {
+            let outgoing = dcel.outgoing_edges(site);
+
+            let mut cell_vertices = outgoing.iter().filter_map(|&e| index.get(&dcel.face(e)).cloned()).collect::<Vec<_>>();
+            let mut cell_edges = outgoing.iter().filter_map(|&e| {
+                edge_index.get(&e).or_else(|| dcel.twin(e).and_then(|t| edge_index.get(&t))).cloned()
+            }).collect::<Vec<_>>();
+
+            if clockwise {
+                cell_vertices.reverse();
+                cell_edges.reverse();
+            }
+
+            VoronoiCell { site, vertices: cell_vertices, edges: cell_edges }
+        }).collect();
+
+        Ok(Voronoi { vertices, edges, cells })
+    }
+
+    /// Without any triangle between the sites, they are all collinear, and the cells are strips
+    /// separated by parallel lines.
+    fn collinear(d: &Delaunay<Point2D>) -> Voronoi {
+        let mut sites = d.vertices();
+        sites.sort_by(|&a, &b| {
+            let (pa, pb) = (d.point(a), d.point(b));
+            (pa.x, pa.y).partial_cmp(&(pb.x, pb.y)).unwrap_or(Ordering::Equal)
+        });
+
+        let edges = sites.windows(2).map(|w| {
+            let (pa, pb) = (d.point(w[0]), d.point(w[1]));
+            let middle = Point2D::new((pa.x + pb.x) / 2.0, (pa.y + pb.y) / 2.0);
+            let direction = Point2D::new(pa.y - pb.y, pb.x - pa.x);
+            VoronoiEdge { sites: (w[0], w[1]), kind: VoronoiEdgeKind::Line(middle, direction) }
+        }).collect::<Vec<_>>();
+
+        let position = sites.iter().enumerate().map(|(i, &v)| (v, i)).collect::<HashMap<_, _>>();
+        let cells = d.vertices().into_iter().map(|site| {
+            let i = position[&site];
+            let cell_edges = (i.saturating_sub(1)..(i + 1).min(edges.len())).collect();
+            VoronoiCell { site, vertices: Vec::new(), edges: cell_edges }
+        }).collect();
+
+        Voronoi { vertices: Vec::new(), edges, cells }
+    }
+
+    /// The Voronoi vertices, i.e. the circumcenters of the Delaunay triangles.
+    pub fn vertices(&self) -> &[Point2D] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> &[VoronoiEdge] {
+        &self.edges
+    }
+
+    /// One cell per site, in the order of `Delaunay::vertices`.
+    pub fn cells(&self) -> &[VoronoiCell] {
+        &self.cells
+    }
+}
+
+impl VoronoiCell {
+    /// Returns true if the cell is a bounded polygon, i.e. it has edges and none of them is a ray
+    /// or a line.
+    pub fn is_bounded(&self, voronoi: &Voronoi) -> bool {
+        !self.edges.iter().any(|&e| match voronoi.edges[e].kind {
+            VoronoiEdgeKind::Ray(..) | VoronoiEdgeKind::Line(..) => true,
+            VoronoiEdgeKind::Segment(..) => false,
+        }) && !self.edges.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn domain() -> Triangle<Point2D> {
+        Triangle::new(Point2D::new(-1000.0, -1000.0), Point2D::new(1000.0, -1000.0), Point2D::new(0.0, 1000.0))
+    }
+
+    fn distance(a: &Point2D, b: &Point2D) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn voronoi_of_square_with_center() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (10.0, 10.0), (5.0, 5.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+
+        let v = Voronoi::new(&d).unwrap();
+        let center = &v.cells()[4];
+
+        assert_eq!(v.vertices().len(), 4);
+        assert_eq!(v.edges().len(), 8);
+        assert_eq!(center.site, d.vertices()[4]);
+        assert!(center.is_bounded(&v));
+        assert_eq!(center.edges.len(), 4);
+
+        // Compare independently of the starting vertex
+        let mut cell = center.vertices.iter().map(|&i| v.vertices()[i]).collect::<Vec<_>>();
+        let first = cell.iter().position(|p| *p == Point2D::new(5.0, 0.0)).unwrap();
+        cell.rotate_left(first);
+        assert_eq!(cell, vec!(Point2D::new(5.0, 0.0), Point2D::new(10.0, 5.0), Point2D::new(5.0, 10.0), Point2D::new(0.0, 5.0)));
+        assert!(v.cells()[..4].iter().all(|c| !c.is_bounded(&v)));
+    }
+
+    #[test]
+    fn voronoi_after_removal() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (10.0, 10.0)] {
+            d.insert(Point2D::new(x, y));
+        }
+        let center = d.insert(Point2D::new(5.0, 5.0)).unwrap();
+
+        assert!(Voronoi::new(&d).unwrap().cells().iter().any(|c| c.site == center));
+
+        d.remove(center);
+        let v = Voronoi::new(&d).unwrap();
+
+        assert_eq!(v.cells().len(), 4);
+        assert!(v.cells().iter().all(|c| c.site != center));
+        assert_eq!(v.vertices().len(), 2);
+        assert!(v.vertices().iter().all(|p| *p == Point2D::new(5.0, 5.0)));
+    }
+
+    #[test]
+    fn voronoi_edges_are_equidistant_to_their_sites() {
+        fn voronoi_edges_are_equidistant_to_their_sites(points: Vec<(f64, f64)>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in &points {
+                d.insert(Point2D::new(x % 100.0, y % 100.0));
+            }
+
+            let v = Voronoi::new(&d).unwrap();
+            let sites = d.sites();
+
+            let equidistant = |p: Point2D, (a, b): (VertexHandle, VertexHandle)| {
+                let (da, db) = (distance(&p, &d.point(a)), distance(&p, &d.point(b)));
+                (da - db).abs() <= 1e-6 * da.max(1.0)
+            };
+
+            TestResult::from_bool(v.edges().iter().all(|e| match e.kind {
+                VoronoiEdgeKind::Segment(i, j) => equidistant(v.vertices()[i], e.sites) && equidistant(v.vertices()[j], e.sites),
+                VoronoiEdgeKind::Ray(i, _) => equidistant(v.vertices()[i], e.sites),
+                VoronoiEdgeKind::Line(p, _) => equidistant(p, e.sites),
+            }) && v.cells().len() == sites.len())
+        }
+        quickcheck(voronoi_edges_are_equidistant_to_their_sites as fn(points: Vec<(f64, f64)>) -> TestResult)
+    }
+
+    /// Checks that no site lies inside the circumcircle of the triangle of any Voronoi vertex,
+    /// that the points along the rays and lines are closest to the two sites they separate, and
+    /// that the diagram satisfies Euler's formula, with all rays and lines meeting at infinity.
+    fn is_voronoi_diagram(d: &Delaunay<Point2D>, v: &Voronoi) -> bool {
+        let sites = d.sites();
+
+        let empty_circles = (0..v.vertices().len()).all(|i| {
+            let around = v.cells().iter().filter(|c| c.vertices.contains(&i)).map(|c| d.point(c.site)).collect::<Vec<_>>();
+            around.len() >= 3 && {
+                let t = Triangle::new(around[0], around[1], around[2]);
+                sites.iter().all(|p| t.in_circle_test(p) != Some(InCircleLocation::Inside))
+            }
+        });
+
+        let is_nearest = |q: Point2D, (a, b): (VertexHandle, VertexHandle)| {
+            let closest = sites.iter().map(|s| distance(&q, s)).fold(f64::INFINITY, f64::min);
+            let (da, db) = (distance(&q, &d.point(a)), distance(&q, &d.point(b)));
+            da.max(db) <= closest + 1e-9 * da
+        };
+        let nearest = v.edges().iter().all(|e| match e.kind {
+            VoronoiEdgeKind::Ray(i, direction) => {
+                let p = v.vertices()[i];
+                is_nearest(Point2D::new(p.x + direction.x, p.y + direction.y), e.sites)
+            },
+            VoronoiEdgeKind::Line(p, direction) => {
+                is_nearest(p, e.sites) && is_nearest(Point2D::new(p.x + direction.x, p.y + direction.y), e.sites)
+            },
+            VoronoiEdgeKind::Segment(..) => true,
+        });
+
+        // Without sites, there is no face at infinity either
+        empty_circles && nearest && (sites.is_empty() || v.edges().len() + 1 == v.vertices().len() + v.cells().len())
+    }
+
+    #[test]
+    fn circumcenter_outside_the_domain() {
+        // The circumcircle of the flat triangle contains the top vertex of the domain
+        let mut d = Delaunay::new(domain()).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (10.0, 0.0), (5.0, -0.01)] {
+            d.insert(Point2D::new(x, y));
+        }
+
+        let v = Voronoi::new(&d).unwrap();
+
+        assert_eq!(v.vertices().len(), 1);
+        assert!(distance(&v.vertices()[0], &Point2D::new(5.0, 1249.995)) < 1e-6);
+        assert_eq!(v.edges().len(), 3);
+        assert!(v.edges().iter().all(|e| matches!(e.kind, VoronoiEdgeKind::Ray(0, _))));
+        assert!(v.cells().iter().all(|c| c.vertices == vec!(0) && c.edges.len() == 2 && !c.is_bounded(&v)));
+        assert!(is_voronoi_diagram(&d, &v));
+
+        // The triangulation itself is left alone
+        assert_eq!(d.constraints().len(), 0);
+    }
+
+    #[test]
+    fn nearly_collinear_sites() {
+        fn nearly_collinear_sites(points: Vec<(f64, f64)>) -> TestResult {
+            let mut d = Delaunay::new(domain()).unwrap();
+            for &(x, y) in &points {
+                d.insert(Point2D::new(x % 100.0, (y % 1.0) * 1e-3));
+            }
+
+            let v = Voronoi::new(&d).unwrap();
+            TestResult::from_bool(is_voronoi_diagram(&d, &v))
+        }
+        quickcheck(nearly_collinear_sites as fn(points: Vec<(f64, f64)>) -> TestResult)
+    }
+
+    #[test]
+    fn collinear_sites() {
+        let mut d = Delaunay::new(domain()).unwrap();
+        let a = d.insert(Point2D::new(0.0, 0.0)).unwrap();
+
+        let v = Voronoi::new(&d).unwrap();
+        assert!(v.vertices().is_empty() && v.edges().is_empty());
+        assert_eq!(v.cells(), &[VoronoiCell { site: a, vertices: vec!(), edges: vec!() }][..]);
+        assert!(is_voronoi_diagram(&d, &v));
+
+        let c = d.insert(Point2D::new(20.0, 10.0)).unwrap();
+        let v = Voronoi::new(&d).unwrap();
+        assert!(v.vertices().is_empty());
+        assert_eq!(v.edges(), &[VoronoiEdge { sites: (a, c), kind: VoronoiEdgeKind::Line(Point2D::new(10.0, 5.0), Point2D::new(-10.0, 20.0)) }][..]);
+        assert!(v.cells().iter().all(|c| c.edges == vec!(0) && !c.is_bounded(&v)));
+        assert!(is_voronoi_diagram(&d, &v));
+
+        // Inserted out of order along the line
+        let b = d.insert(Point2D::new(10.0, 5.0)).unwrap();
+        let e = d.insert(Point2D::new(-20.0, -10.0)).unwrap();
+        let v = Voronoi::new(&d).unwrap();
+        assert!(v.vertices().is_empty());
+        assert_eq!(v.edges().iter().map(|e| e.sites).collect::<Vec<_>>(), vec!((e, a), (a, b), (b, c)));
+        assert_eq!(v.edges()[0].kind, VoronoiEdgeKind::Line(Point2D::new(-10.0, -5.0), Point2D::new(-10.0, 20.0)));
+        assert_eq!(v.cells().iter().map(|c| c.edges.clone()).collect::<Vec<_>>(), vec!(vec!(0, 1), vec!(2), vec!(1, 2), vec!(0)));
+        assert!(is_voronoi_diagram(&d, &v));
+    }
+}
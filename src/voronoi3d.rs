@@ -0,0 +1,279 @@
+use geometry::*;
+use delaunay3d::*;
+
+/// A bounded 3D Voronoi cell, a convex polyhedron around its site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoronoiCell3D {
+    /// Index of the site in `Delaunay3D::vertices`.
+    pub site: usize,
+    /// The Voronoi vertices of the cell, i.e. the circumcenters of the tetrahedra around the site.
+    pub vertices: Vec<Point3D>,
+    /// Each face as indices into `vertices`, ordered counter-clockwise seen from outside.
+    pub faces: Vec<Vec<usize>>,
+    /// The site of the cell on the other side of each face.
+    pub neighbors: Vec<usize>,
+    pub face_areas: Vec<f64>,
+    pub volume: f64,
+    pub centroid: Point3D,
+}
+
+/// The 3D Voronoi diagram of the sites of a Delaunay tetrahedralization.
+///
+/// Cells of sites on the convex hull are unbounded and are not computed.
+///
+/// A vertex of the domain can lie inside the circumsphere of a tetrahedron of the sites, e.g. of
+/// a flat tetrahedron on the hull, and then replaces it in the tetrahedralization. The sites are
+/// therefore tetrahedralized again with the domain at infinity, where the tetrahedra touching
+/// the domain are exactly those outside the convex hull.
+pub struct Voronoi3D {
+    cells: Vec<Option<VoronoiCell3D>>,
+}
+
+impl Voronoi3D {
+    pub fn new(d: &Delaunay3D) -> Voronoi3D {
+        let mut unbounded = Delaunay3D::unbounded();
+        for (i, &p) in d.sites().iter().enumerate() {
+            let index = unbounded.insert(p);
+            debug_assert_eq!(index, Some(i + 4));
+        }
+        let d = &unbounded;
+
+        let tetrahedra = d.tetrahedron_indices();
+
+        let mut stars = vec!(Vec::new(); d.vertices().len());
+        for (i, t) in tetrahedra.iter().enumerate() {
+            for &v in t {
+                stars[v].push(i);
+            }
+        }
+
+        let cells = (4..d.vertices().len()).map(|site| {
+            let star = &stars[site];
+
+            if star.iter().any(|&i| tetrahedra[i].iter().any(|&v| v < 4)) {
+                return None;
+            }
+
+            Some(cell(d, site, star))
+        }).collect();
+
+        Voronoi3D { cells }
+    }
+
+    /// One entry per site, in the order of `Delaunay3D::sites`. Unbounded cells are `None`.
+    pub fn cells(&self) -> &[Option<VoronoiCell3D>] {
+        &self.cells
+    }
+}
+
+type Vector = (f64, f64, f64);
+
+fn sub(a: &Point3D, b: &Point3D) -> Vector {
+    (a.x - b.x, a.y - b.y, a.z - b.z)
+}
+
+fn cross(u: Vector, v: Vector) -> Vector {
+    (u.1 * v.2 - u.2 * v.1, u.2 * v.0 - u.0 * v.2, u.0 * v.1 - u.1 * v.0)
+}
+
+fn dot(u: Vector, v: Vector) -> f64 {
+    u.0 * v.0 + u.1 * v.1 + u.2 * v.2
+}
+
+fn cell(d: &Delaunay3D, site: usize, star: &[usize]) -> VoronoiCell3D {
+    let tetrahedra = d.tetrahedron_indices();
+    let p = d.vertices()[site];
+
+    let vertices = star.iter().map(|&i| {
+        let t = tetrahedra[i];
+        let v = d.vertices();
+        Tetrahedron::new(v[t[0]], v[t[1]], v[t[2]], v[t[3]]).circumcenter()
+    }).collect::<Vec<_>>();
+    let local = |i: usize| star.iter().position(|&s| s == i).unwrap();
+
+    let mut neighbors = star.iter().flat_map(|&i| tetrahedra[i].to_vec()).filter(|&v| v != site).collect::<Vec<_>>();
+    neighbors.sort();
+    neighbors.dedup();
+
+    let mut faces = Vec::with_capacity(neighbors.len());
+    let mut face_areas = Vec::with_capacity(neighbors.len());
+    let mut volume = 0.0;
+    let mut moment = (0.0, 0.0, 0.0);
+
+    for &other in &neighbors {
+        // The face dual to the edge (site, other) connects the circumcenters of the ring of
+        // tetrahedra around that edge.
+        let start = *star.iter().find(|&&i| tetrahedra[i].contains(&other)).unwrap();
+        let mut ring = vec!(start);
+        let mut previous = None;
+        let mut current = start;
+
+        loop {
+            let t = tetrahedra[current];
+            let next = (0..4).filter(|&k| t[k] != site && t[k] != other)
+                             .filter_map(|k| d.neighbors(current)[k])
+                             .find(|&n| Some(n) != previous)
+                             .unwrap();
+
+            if next == start {
+                break;
+            }
+
+            ring.push(next);
+            previous = Some(current);
+            current = next;
+        }
+
+        let mut face = ring.into_iter().map(&local).collect::<Vec<_>>();
+
+        let p0 = vertices[face[0]];
+        let mut area = (0.0, 0.0, 0.0);
+        for w in face[1..].windows(2) {
+            let c = cross(sub(&vertices[w[0]], &p0), sub(&vertices[w[1]], &p0));
+            area = (area.0 + c.0, area.1 + c.1, area.2 + c.2);
+        }
+
+        // Orient the face outwards, i.e. towards the neighboring site
+        if dot(area, sub(&d.vertices()[other], &p)) < 0.0 {
+            face.reverse();
+            area = (-area.0, -area.1, -area.2);
+        }
+
+        face_areas.push(0.5 * dot(area, area).sqrt());
+
+        // Decompose the cell into tetrahedra with apex at the site
+        let p0 = vertices[face[0]];
+        for w in face[1..].windows(2) {
+            let (p1, p2) = (vertices[w[0]], vertices[w[1]]);
+            let v = dot(sub(&p0, &p), cross(sub(&p1, &p), sub(&p2, &p))) / 6.0;
+
+            volume += v;
+            moment.0 += v * (p.x + p0.x + p1.x + p2.x) / 4.0;
+            moment.1 += v * (p.y + p0.y + p1.y + p2.y) / 4.0;
+            moment.2 += v * (p.z + p0.z + p1.z + p2.z) / 4.0;
+        }
+
+        faces.push(face);
+    }
+
+    VoronoiCell3D {
+        site,
+        vertices,
+        faces,
+        neighbors,
+        face_areas,
+        volume,
+        centroid: Point3D::new(moment.0 / volume, moment.1 / volume, moment.2 / volume),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::{TestResult, quickcheck};
+
+    fn domain() -> Tetrahedron<Point3D> {
+        Tetrahedron::new(Point3D::new(-100.0, -100.0, -100.0),
+                         Point3D::new(300.0, -100.0, -100.0),
+                         Point3D::new(-100.0, 300.0, -100.0),
+                         Point3D::new(-100.0, -100.0, 300.0))
+    }
+
+    #[test]
+    fn voronoi_cell_of_octahedron_center_is_cube() {
+        let mut d = Delaunay3D::new(domain()).unwrap();
+        let center = d.insert(Point3D::new(0.0, 0.0, 0.0)).unwrap();
+        for &(x, y, z) in &[(1.0, 0.0, 0.0), (-1.0, 0.0, 0.0), (0.0, 1.0, 0.0),
+                            (0.0, -1.0, 0.0), (0.0, 0.0, 1.0), (0.0, 0.0, -1.0)] {
+            d.insert(Point3D::new(x, y, z));
+        }
+
+        let v = Voronoi3D::new(&d);
+        let cell = v.cells()[center - 4].as_ref().unwrap();
+
+        assert_eq!(cell.site, center);
+        assert_eq!(cell.vertices.len(), 8);
+        assert_eq!(cell.neighbors, vec!(5, 6, 7, 8, 9, 10));
+        assert!(cell.faces.iter().all(|f| f.len() == 4));
+        assert!(cell.face_areas.iter().all(|&a| (a - 1.0).abs() < 1e-12));
+        assert!((cell.volume - 1.0).abs() < 1e-12);
+        assert!(cell.centroid.x.abs() < 1e-12 && cell.centroid.y.abs() < 1e-12 && cell.centroid.z.abs() < 1e-12);
+        assert!(v.cells()[1..].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn voronoi_cells_are_closed() {
+        fn voronoi_cells_are_closed(points: Vec<(f64, f64, f64)>) -> TestResult {
+            let mut d = Delaunay3D::new(domain()).unwrap();
+            for &(x, y, z) in &points {
+                d.insert(Point3D::new(x % 50.0, y % 50.0, z % 50.0));
+            }
+
+            let v = Voronoi3D::new(&d);
+
+            // The outward area vectors of the faces of a closed polyhedron sum up to zero. Each
+            // face is perpendicular to the Delaunay edge to its neighbor.
+            TestResult::from_bool(v.cells().iter().filter_map(|c| c.as_ref()).all(|c| {
+                let mut total = (0.0, 0.0, 0.0);
+                for (&other, &area) in c.neighbors.iter().zip(&c.face_areas) {
+                    let n = sub(&d.vertices()[other], &d.vertices()[c.site]);
+                    let s = area / dot(n, n).sqrt();
+                    total = (total.0 + s * n.0, total.1 + s * n.1, total.2 + s * n.2);
+                }
+                let scale = c.face_areas.iter().fold(1.0, |a: f64, &b| a.max(b));
+
+                c.volume > 0.0 && dot(total, total).sqrt() <= 1e-6 * scale
+            }))
+        }
+        quickcheck(voronoi_cells_are_closed as fn(points: Vec<(f64, f64, f64)>) -> TestResult)
+    }
+
+    /// Checks that no site is closer to a vertex of the cell than its own site.
+    fn vertices_are_nearest_to_the_site(d: &Delaunay3D, c: &VoronoiCell3D) -> bool {
+        let site = d.vertices()[c.site];
+        c.vertices.iter().all(|q| {
+            let distance = |p: &Point3D| dot(sub(q, p), sub(q, p)).sqrt();
+            let own = distance(&site);
+            d.sites().iter().all(|p| own <= distance(p) + 1e-9 * own)
+        })
+    }
+
+    #[test]
+    fn nearly_coplanar_sites() {
+        // The circumspheres of the flat tetrahedra reach far beyond the domain, and so do the
+        // vertices of the cell of the center
+        let mut d = Delaunay3D::new(domain()).unwrap();
+        let center = d.insert(Point3D::new(0.0, 0.0, 0.0)).unwrap();
+        for i in 0..8 {
+            let (x, y) = (if i % 2 == 0 { -10.0 } else { 10.0 }, if i / 2 % 2 == 0 { -10.0 } else { 10.0 });
+            let z = if i < 4 { -0.01 } else { 0.01 } + f64::from(i) * 1e-4;
+            d.insert(Point3D::new(x, y, z));
+        }
+
+        let v = Voronoi3D::new(&d);
+        let cell = v.cells()[center - 4].as_ref().unwrap();
+
+        assert_eq!(cell.neighbors, (5..13).collect::<Vec<_>>());
+        assert!(cell.vertices.iter().any(|p| p.z.abs() > 1000.0));
+        assert!(vertices_are_nearest_to_the_site(&d, cell));
+        assert!(v.cells()[1..].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn nearly_coplanar_cells_are_nearest_to_their_sites() {
+        fn nearly_coplanar_cells_are_nearest_to_their_sites(points: Vec<(f64, f64, f64)>) -> TestResult {
+            let mut d = Delaunay3D::new(domain()).unwrap();
+            for &(x, y, z) in &points {
+                d.insert(Point3D::new(x % 50.0, y % 50.0, (z % 1.0) * 1e-3));
+            }
+
+            let v = Voronoi3D::new(&d);
+
+            TestResult::from_bool(v.cells().iter().filter_map(|c| c.as_ref()).all(|c| {
+                c.volume > 0.0 && vertices_are_nearest_to_the_site(&d, c)
+            }))
+        }
+        quickcheck(nearly_coplanar_cells_are_nearest_to_their_sites as fn(points: Vec<(f64, f64, f64)>) -> TestResult)
+    }
+}